@@ -19,10 +19,19 @@ mod ddrom;
 
 mod driver;
 pub use driver::cmd;
+pub use driver::CgramPlan;
+pub use driver::Controller;
 pub use driver::Driver;
+pub use driver::Geometry;
+pub use driver::MultiDriver;
 pub use driver::Pins;
+pub use driver::{Capabilities, Unsupported};
+pub use driver::{OpStats, PerfStats, SelfTest};
 
 pub mod canvas;
 
+#[cfg(feature = "latin1")]
+mod font;
+
 mod bitmap;
-pub use bitmap::Bitmap;
+pub use bitmap::{Bitmap, Bounds, CompositeOp, Gamma};
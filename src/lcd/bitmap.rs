@@ -1,5 +1,16 @@
+use core::array::from_fn;
 use core::fmt;
 use core::iter::zip;
+use core::ops::{BitAnd, BitOr, BitXor};
+
+use super::ddrom;
+
+#[cfg(feature = "embedded-graphics")]
+use embedded_graphics::{
+    image::{GetPixel, ImageRaw},
+    pixelcolor::BinaryColor,
+    prelude::{OriginDimensions, Point, Size},
+};
 
 /// A 5 by 8 black-and-white image represented as a matrix of bits
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
@@ -22,9 +33,14 @@ impl Bitmap {
     /// If the character is not present, a blank space will
     /// be generated.
     ///
-    /// The map of the characters is [`CHAR2BITMAP`]
+    /// The map of the characters is [`CHAR2BITMAP`].
+    /// With the `latin1` feature the rest of the Latin-1 characters
+    /// are rendered too, even though they are not in the DDROM.
     pub fn render(ch: char) -> Self {
-        MAP.get(&ch).copied().unwrap_or_default()
+        let glyph = MAP.get(&ch).copied();
+        #[cfg(feature = "latin1")]
+        let glyph = glyph.or_else(|| super::font::rasterize(ch));
+        glyph.unwrap_or_default()
     }
 
     /// Convert the bitmap into and array of bytes
@@ -32,6 +48,156 @@ impl Bitmap {
         self.0.map(|l| l.0)
     }
 
+    /// Packs the 40 pixels of the bitmap into a single integer
+    ///
+    /// The line `y` occupies the bits from `5 * y` to `5 * y + 4`,
+    /// so two bitmaps are equal only if their packed values are
+    pub fn pack(self) -> u64 {
+        self.0
+            .iter()
+            .enumerate()
+            .fold(0, |acc, (y, l)| acc | u64::from(l.0) << (5 * y))
+    }
+
+    /// Unpacks a bitmap created with [`pack`](Self::pack)
+    ///
+    /// The bits above the 40th are ignored
+    pub fn unpack(packed: u64) -> Self {
+        Self(from_fn(|y| Bitline::new((packed >> (5 * y)) as u8)))
+    }
+
+    /// Creates a [`Bitmap`] with only the given pixels lit
+    ///
+    /// The points are given as `(x, y)`, with `(0, 0)` being the top-left
+    /// pixel, and the ones outside of the bitmap are ignored
+    pub fn from_points(points: impl IntoIterator<Item = (u8, u8)>) -> Self {
+        let mut rows = [0; 8];
+        for (x, y) in points {
+            if x < 5 && y < 8 {
+                rows[usize::from(y)] |= 1 << (4 - x);
+            }
+        }
+        Self::new(rows)
+    }
+
+    /// Returns the lit pixels of the [`Bitmap`] as `(x, y)` points
+    ///
+    /// This is the inverse of [`from_points`](Self::from_points),
+    /// the points are ordered by row and then by column
+    pub fn pixels(self) -> impl Iterator<Item = (u8, u8)> {
+        let rows = self.raw();
+        (0..8u8).flat_map(move |y| {
+            (0..5u8)
+                .filter_map(move |x| (rows[usize::from(y)] >> (4 - x) & 1 != 0).then_some((x, y)))
+        })
+    }
+
+    /// Returns whether the pixel at column `x` and row `y` is lit
+    ///
+    /// Like in [`from_points`](Self::from_points) the coordinates outside
+    /// of the bitmap are ignored, so they are always unlit
+    pub fn get(self, x: u8, y: u8) -> bool {
+        x < 5 && y < 8 && self.0[usize::from(y)].0 >> (4 - x) & 1 != 0
+    }
+
+    /// Lights the pixel at column `x` and row `y` if `on`, otherwise clears it
+    ///
+    /// Like in [`from_points`](Self::from_points) the coordinates outside
+    /// of the bitmap are ignored, leaving it untouched
+    pub fn set(&mut self, x: u8, y: u8, on: bool) {
+        if x >= 5 || y >= 8 {
+            return;
+        }
+        let line = &mut self.0[usize::from(y)];
+        let bit = 1 << (4 - x);
+        *line = Bitline::new(if on { line.0 | bit } else { line.0 & !bit });
+    }
+
+    /// Returns the pixels of the [`Bitmap`], `true` where they are lit
+    ///
+    /// The pixels are ordered by row and then by column, like the
+    /// ones of [`pixels`](Self::pixels), so `(x, y)` is at `5 * y + x`
+    pub fn to_bools(self) -> [bool; 40] {
+        let rows = self.raw();
+        from_fn(|i| rows[i / 5] >> (4 - i % 5) & 1 != 0)
+    }
+
+    /// Creates a [`Bitmap`] from its pixels, as returned by [`to_bools`](Self::to_bools)
+    pub fn from_bools(bools: &[bool; 40]) -> Self {
+        Self::new(from_fn(|y| {
+            let row = &bools[5 * y..5 * y + 5];
+            row.iter().fold(0, |acc, &on| acc << 1 | u8::from(on))
+        }))
+    }
+
+    /// Converts a grayscale image into a [`Bitmap`]
+    ///
+    /// The image is given as rows of levels, from `0` (black) to `255` (white),
+    /// with the first one being the leftmost. Each level is first corrected
+    /// with the given [`Gamma`] curve, and the pixel is turned on when the
+    /// result is darker than (i.e. below) `threshold`.
+    pub fn blit_gray(gray: &[[u8; 5]; 8], threshold: u8, gamma: Gamma) -> Self {
+        Self(gray.map(|row| {
+            let bits = row.map(|level| u8::from(gamma.apply(level) < threshold));
+            Bitline::new(bits.into_iter().fold(0, |acc, bit| acc << 1 | bit))
+        }))
+    }
+
+    /// Number of lit pixels in each column, starting from the leftmost one
+    pub fn column_weights(self) -> [u8; 5] {
+        from_fn(|x| self.0.iter().map(|l| l.0 >> (4 - x) & 1).sum())
+    }
+
+    /// Number of lit pixels in each row, starting from the top one
+    pub fn row_weights(self) -> [u8; 8] {
+        self.0.map(|l| l.0.count_ones() as u8)
+    }
+
+    /// Checks whether none of the pixels is lit
+    pub fn is_blank(self) -> bool {
+        self.0.iter().all(|l| l.0 == 0)
+    }
+
+    /// Number of lit pixels, which is the [`distance`](Self::distance) from a blank bitmap
+    pub fn density(self) -> u32 {
+        self.distance(Self::default())
+    }
+
+    /// Returns the tightest box containing all the lit pixels
+    ///
+    /// The box is returned as `(x0, x1, y0, y1)`, with both ends included
+    /// and `(0, 0)` being the top-left pixel, or [`None`] if the bitmap is blank
+    pub fn ink_bounds(self) -> Option<(u8, u8, u8, u8)> {
+        let bounds = self.bounds()?;
+        Some((bounds.min_x, bounds.max_x, bounds.min_y, bounds.max_y))
+    }
+
+    /// Returns the tightest box containing all the lit pixels
+    ///
+    /// This is the same as [`ink_bounds`](Self::ink_bounds), but the box is
+    /// returned as a [`Bounds`]. Returns [`None`] if the bitmap is blank.
+    /// The rows are scanned once, collecting the lit columns of all of them.
+    pub fn bounds(self) -> Option<Bounds> {
+        let mut columns = 0;
+        let mut rows = None;
+        for (y, line) in (0..8u8).zip(self.0) {
+            if line.0 != 0 {
+                columns |= line.0;
+                rows = Some((rows.map_or(y, |(y0, _)| y0), y));
+            }
+        }
+        let (y0, y1) = rows?;
+        // NOTE:
+        // Only the 5 least significant bits are used, with the leftmost column
+        // being the most significant one
+        Some(Bounds {
+            min_x: columns.leading_zeros() as u8 - 3,
+            min_y: y0,
+            max_x: 4 - columns.trailing_zeros() as u8,
+            max_y: y1,
+        })
+    }
+
     /// Calculate the distance from the two [`Bitmap`]s
     ///
     /// The distance is computed by counting the number
@@ -39,6 +205,244 @@ impl Bitmap {
     pub fn distance(self, other: Self) -> u32 {
         zip(self.0, other.0).map(|(a, b)| a.distance(b)).sum()
     }
+
+    /// Calculate the distance from the two [`Bitmap`]s for each row
+    ///
+    /// The sum of the distances of the rows is the [`distance`](Self::distance)
+    pub fn row_distances(self, other: Self) -> [u32; 8] {
+        from_fn(|y| self.0[y].distance(other.0[y]))
+    }
+
+    /// Finds the glyph of the **DDROM** that most closely resembles this one
+    ///
+    /// Returns the address of the glyph with the smallest [`distance`](Self::distance)
+    /// and that distance. When more glyphs are at the same distance the one
+    /// at the lowest address is returned, so the result doesn't depend on
+    /// the order of the **DDROM** map.
+    pub fn nearest_in_ddrom(self) -> (u8, u32) {
+        ddrom::all()
+            .map(|(glyph, code)| (code, self.distance(glyph)))
+            .min_by_key(|&(code, distance)| (distance, code))
+            .unwrap()
+    }
+
+    /// Compares the two [`Bitmap`]s considering only the pixels set in `mask`
+    ///
+    /// The mask is given as rows, like [`raw`](Self::raw), so for example
+    /// `[0b11111, 0b11111, 0b11111, 0b11111, 0b11111, 0b11111, 0b11111, 0]`
+    /// ignores the last row, where the cursor is shown
+    pub fn eq_masked(self, other: Self, mask: [u8; 8]) -> bool {
+        zip(zip(self.raw(), other.raw()), mask).all(|((a, b), mask)| (a ^ b) & mask == 0)
+    }
+
+    /// Returns the glyph at the given step of a transition from `from` to `to`
+    ///
+    /// The pixels that differ between the two are flipped one after the other,
+    /// from the top row to the bottom one and from left to right, so that
+    /// after `total` steps the result is `to`. At each step the number of
+    /// flipped pixels is proportional to `step`, rounded down.
+    pub fn blend_step(from: Self, to: Self, step: u32, total: u32) -> Self {
+        let diff = from.pack() ^ to.pack();
+        let count = diff.count_ones();
+        let flips = match total {
+            0 => count,
+            // NOTE: computed in `u64` as `count * step` may not fit in a `u32`
+            total => (u64::from(count) * u64::from(step.min(total)) / u64::from(total)) as u32,
+        };
+
+        let mut rows = from.raw();
+        let pixels = (0..8).flat_map(|y| (0..5).rev().map(move |bit| (y, bit)));
+        pixels
+            .filter(|&(y, bit)| diff >> (5 * y + bit) & 1 != 0)
+            .take(flips as usize)
+            .for_each(|(y, bit)| rows[y] ^= 1 << bit);
+        Self::new(rows)
+    }
+
+    /// Scrolls the [`Bitmap`] up by `px` pixels, bringing `next` in from below
+    ///
+    /// This is a frame of the transition that rolls `next` in place of `self`,
+    /// which is `self` with `px` equal to `0` and `next` with `px` from `8` on.
+    pub fn roll(self, next: Self, px: u8) -> Self {
+        let px = usize::from(px.min(8));
+        Self(from_fn(|y| match y + px {
+            y if y < 8 => self.0[y],
+            y => next.0[y - 8],
+        }))
+    }
+
+    /// Flips the [`Bitmap`] left-to-right
+    ///
+    /// Only the 5 least significant bits of each line are pixels, so after
+    /// reversing the whole byte they are moved back and masked by [`Bitline::new`]
+    pub fn mirror_horizontal(self) -> Self {
+        Self(self.0.map(|l| Bitline::new(l.0.reverse_bits() >> 3)))
+    }
+
+    /// Flips the [`Bitmap`] upside down
+    ///
+    /// All the 8 rows are reversed, the last one included, so the row of the
+    /// cursor (usually blank in the glyphs of the font) becomes the first one.
+    /// Combined with [`mirror_horizontal`](Self::mirror_horizontal) this
+    /// rotates the bitmap by 180°.
+    pub fn flip_vertical(self) -> Self {
+        let mut lines = self.0;
+        lines.reverse();
+        Self(lines)
+    }
+
+    /// Rotates the [`Bitmap`] by 90° clockwise
+    ///
+    /// The rotated image is 8 pixels wide and 5 tall, so it doesn't fit the
+    /// cell: only the first 5 rows are kept, which become the columns from
+    /// the right to the left, while the others are clipped. The columns
+    /// become the first 5 rows, and the last 3 rows are left blank.
+    pub fn rotate_cw(self) -> Self {
+        let rows = self.raw();
+        Self(from_fn(|y| {
+            let bits = (0..5).map(|x| match y {
+                y if y < 5 => rows[4 - x] >> (4 - y) & 1,
+                _ => 0,
+            });
+            Bitline::new(bits.fold(0, |acc, bit| acc << 1 | bit))
+        }))
+    }
+
+    /// Grows the lit region of the [`Bitmap`] by one pixel
+    ///
+    /// A pixel is set if it, or any of its 4 neighbors, is set.
+    /// The pixels outside of the bitmap are considered off.
+    pub fn dilate(self) -> Self {
+        let rows = self.raw();
+        Self(from_fn(|y| {
+            let (up, down) = Self::neighbors(&rows, y);
+            Bitline::new(rows[y] | rows[y] << 1 | rows[y] >> 1 | up | down)
+        }))
+    }
+
+    /// Shrinks the lit region of the [`Bitmap`] by one pixel
+    ///
+    /// A pixel is kept only if all of its 4 neighbors are set.
+    /// The pixels outside of the bitmap are considered off, so
+    /// the ones on the edges are always turned off.
+    pub fn erode(self) -> Self {
+        let rows = self.raw();
+        Self(from_fn(|y| {
+            let (up, down) = Self::neighbors(&rows, y);
+            Bitline::new(rows[y] & rows[y] << 1 & rows[y] >> 1 & up & down)
+        }))
+    }
+
+    /// Returns the 1 pixel border around the lit region of the [`Bitmap`]
+    ///
+    /// The border is made of the pixels that [`dilate`](Self::dilate) turns on,
+    /// so, just like there, nothing is drawn outside of the bitmap edges.
+    pub fn outline(self) -> Self {
+        let dilated = self.dilate();
+        Self(from_fn(|y| Bitline::new(dilated.0[y].0 ^ self.0[y].0)))
+    }
+
+    /// Moves the pixels by `dx` to the right and by `dy` down
+    ///
+    /// Negative values move them to the left and up, and the
+    /// pixels that go outside of the bitmap are clipped
+    pub fn shift(self, dx: i8, dy: i8) -> Self {
+        let rows = self.raw();
+        let row = |y: i32| usize::try_from(y).ok().and_then(|y| rows.get(y)).copied();
+        Self(from_fn(|y| {
+            let row = row(y as i32 - i32::from(dy)).unwrap_or(0);
+            let row = if dx >= 0 {
+                row.checked_shr(dx as u32)
+            } else {
+                row.checked_shl(dx.unsigned_abs().into())
+            };
+            Bitline::new(row.unwrap_or(0))
+        }))
+    }
+
+    /// Moves the region starting at column `x` and row `y` to the top-left
+    ///
+    /// The pixels on the left of `x` and above `y` are dropped, and the
+    /// columns and rows left vacant on the right and at the bottom are blank.
+    /// When `x` is at least 5 or `y` is at least 8 the whole bitmap is
+    /// outside of the region, so the result is blank.
+    pub fn crop(self, x: u8, y: u8) -> Self {
+        Self(from_fn(|row| {
+            let line = self.0.get(row + usize::from(y)).map_or(0, |l| l.0);
+            Bitline::new(line.checked_shl(x.into()).unwrap_or(0))
+        }))
+    }
+
+    /// Draws the lit pixels of `other` over the [`Bitmap`], like an underline
+    /// over a character
+    ///
+    /// This is the same as `self | other`
+    pub fn overlay(self, other: Self) -> Self {
+        self | other
+    }
+
+    /// Combines the pixels of the two [`Bitmap`]s with the given operation
+    pub fn composite(self, other: Self, op: CompositeOp) -> Self {
+        Self(from_fn(|y| {
+            Bitline::new(op.apply(self.0[y].0, other.0[y].0))
+        }))
+    }
+
+    /// Returns the rows above and below the row `y`, or `0` when out of bounds
+    fn neighbors(rows: &[u8; 8], y: usize) -> (u8, u8) {
+        let up = y.checked_sub(1).map_or(0, |y| rows[y]);
+        let down = rows.get(y + 1).copied().unwrap_or(0);
+        (up, down)
+    }
+}
+
+#[cfg(feature = "embedded-graphics")]
+impl Bitmap {
+    /// Converts an `embedded-graphics` image into a [`Bitmap`]
+    ///
+    /// Returns [`None`] if the image is not exactly 5 by 8 pixels
+    pub fn from_image_raw(image: &ImageRaw<'_, BinaryColor>) -> Option<Self> {
+        if image.size() != Size::new(5, 8) {
+            return None;
+        }
+        Some(Self(from_fn(|y| {
+            let bits =
+                (0..5).map(|x| image.pixel(Point::new(x, y as i32)) == Some(BinaryColor::On));
+            Bitline::new(bits.fold(0, |acc, bit| acc << 1 | u8::from(bit)))
+        })))
+    }
+
+    /// Returns the data of the [`Bitmap`] in the format used by [`ImageRaw`]
+    ///
+    /// Each row is stored in a byte starting from the most significant bit,
+    /// so the image can be created with `ImageRaw::new(&data, 5)`.
+    pub fn to_image_raw_data(self) -> [u8; 8] {
+        self.raw().map(|l| l << 3)
+    }
+}
+
+impl BitOr for Bitmap {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(from_fn(|y| Bitline::new(self.0[y].0 | rhs.0[y].0)))
+    }
+}
+
+impl BitAnd for Bitmap {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        Self(from_fn(|y| Bitline::new(self.0[y].0 & rhs.0[y].0)))
+    }
+}
+
+impl BitXor for Bitmap {
+    type Output = Self;
+
+    fn bitxor(self, rhs: Self) -> Self {
+        Self(from_fn(|y| Bitline::new(self.0[y].0 ^ rhs.0[y].0)))
+    }
 }
 
 impl fmt::Display for Bitmap {
@@ -47,6 +451,64 @@ impl fmt::Display for Bitmap {
     }
 }
 
+/// Box containing the lit pixels of a [`Bitmap`], see [`Bitmap::bounds`]
+///
+/// Both ends are included, and `(0, 0)` is the top-left pixel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bounds {
+    pub min_x: u8,
+    pub min_y: u8,
+    pub max_x: u8,
+    pub max_y: u8,
+}
+
+/// Correction curve applied to the gray levels by [`Bitmap::blit_gray`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Gamma {
+    /// Keep the levels as they are
+    #[default]
+    Linear,
+    /// Darken the mid-tones (gamma 2), more pixels are turned on
+    Darken,
+    /// Lighten the mid-tones (gamma 1/2), less pixels are turned on
+    Lighten,
+}
+
+impl Gamma {
+    /// Applies the correction curve to the given gray level
+    pub fn apply(self, level: u8) -> u8 {
+        let square = |v: u8| (u16::from(v) * u16::from(v) / 255) as u8;
+        match self {
+            Self::Linear => level,
+            Self::Darken => square(level),
+            Self::Lighten => 255 - square(255 - level),
+        }
+    }
+}
+
+/// How the pixels of two [`Bitmap`]s are combined by [`Bitmap::composite`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CompositeOp {
+    /// A pixel is set if it's set in either of the two
+    #[default]
+    Or,
+    /// A pixel is set if it's set in only one of the two
+    Xor,
+    /// The pixels are taken from the second one
+    Replace,
+}
+
+impl CompositeOp {
+    /// Combines two lines of pixels
+    pub fn apply(self, below: u8, above: u8) -> u8 {
+        match self {
+            Self::Or => below | above,
+            Self::Xor => below ^ above,
+            Self::Replace => above,
+        }
+    }
+}
+
 /// Line of bits of a [`Bitmap`]
 ///
 /// It's stored in a byte for semplicity but only
@@ -195,3 +657,249 @@ static MAP: phf::Map<char, Bitmap> = phf::phf_map! {
     '→' => bitmap![0b00000, 0b00100, 0b00010, 0b11111, 0b00010, 0b00100, 0b00000, 0b00000],
     '←' => bitmap![0b00000, 0b00100, 0b01000, 0b11111, 0b01000, 0b00100, 0b00000, 0b00000],
 };
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounds() {
+        assert_eq!(Bitmap::default().bounds(), None);
+        let bounds = Bitmap::new([0, 0, 0b00100, 0, 0b01000, 0, 0b00010, 0]).bounds();
+        let expected = Bounds {
+            min_x: 1,
+            min_y: 2,
+            max_x: 3,
+            max_y: 6,
+        };
+        assert_eq!(bounds, Some(expected));
+    }
+
+    #[test]
+    fn ink_bounds() {
+        assert_eq!(Bitmap::default().ink_bounds(), None);
+        let bitmap = Bitmap::new([0, 0, 0b00100, 0, 0b01000, 0, 0b00010, 0]);
+        assert_eq!(bitmap.ink_bounds(), Some((1, 3, 2, 6)));
+    }
+
+    #[test]
+    fn ink_bounds_of_a_single_pixel() {
+        let dot = Bitmap::new([0, 0, 0, 0b00100, 0, 0, 0, 0]);
+        assert_eq!(dot.ink_bounds(), Some((2, 2, 3, 3)));
+        assert_eq!(Bitmap::render(' ').ink_bounds(), None);
+    }
+
+    #[test]
+    fn mirror_horizontal() {
+        let right = Bitmap::render('→');
+        assert_eq!(right.mirror_horizontal(), Bitmap::render('←'));
+        assert_eq!(right.mirror_horizontal().mirror_horizontal(), right);
+    }
+
+    /// Every glyph of the **DDROM** with one pixel flipped is compared with a
+    /// scan of the whole **DDROM**, some of them being at the same distance
+    /// from more glyphs
+    #[test]
+    fn nearest_in_ddrom_ties_to_the_lowest_code() {
+        let mut ties = 0;
+        for (glyph, _) in ddrom::all() {
+            for pixel in 0..40 {
+                let rows = glyph.raw();
+                let bitmap = Bitmap::new(from_fn(|y| {
+                    rows[y] ^ u8::from(pixel / 5 == y) << (pixel % 5)
+                }));
+                let distance = ddrom::all().map(|(g, _)| bitmap.distance(g)).min().unwrap();
+                let nearest: heapless::Vec<_, 256> = ddrom::all()
+                    .filter(|&(g, _)| bitmap.distance(g) == distance)
+                    .map(|(_, code)| code)
+                    .collect();
+                let code = *nearest.iter().min().unwrap();
+                ties += usize::from(nearest.len() > 1);
+                assert_eq!(bitmap.nearest_in_ddrom(), (code, distance));
+            }
+        }
+        assert!(ties > 0);
+    }
+
+    /// Characters of the font whose glyph doesn't match their **DDROM** code
+    ///
+    /// Each mismatch is listed along with the address of the character in the
+    /// **DDROM** and the one found by searching its glyph. When the glyph is not
+    /// found the character would needlessly take a **CGRAM** slot, while when
+    /// it's found at the address of another character it would be shown wrong.
+    ///
+    /// **Note** that some glyphs (e.g. the blank one) are at more than one
+    /// address, so the search finding a different one is not a mismatch by itself
+    #[test]
+    fn font_matches_ddrom() {
+        let mismatches: heapless::Vec<_, 256> = MAP
+            .entries()
+            .filter_map(|(&ch, &glyph)| {
+                let code = ddrom::encode(ch)?;
+                let found = ddrom::search(glyph);
+                let wrong = match found {
+                    None => true,
+                    Some(found) => ddrom::decode(found).is_some_and(|other| other != ch),
+                };
+                wrong.then_some((ch, code, found))
+            })
+            .collect();
+        assert!(mismatches.is_empty(), "(char, code, found): {mismatches:?}");
+    }
+
+    /// Characters of the font not in the **DDROM** whose closest glyph there is too far
+    ///
+    /// These are the characters that [`Canvas::approx`](crate::lcd::canvas::Canvas::approx)
+    /// would turn into something unrecognizable when they don't fit in the
+    /// **CGRAM**. Each one is listed along with the address of the closest
+    /// glyph and its distance.
+    #[test]
+    fn font_approximates_within_distance() {
+        /// Largest [`distance`](Bitmap::distance) at which the closest
+        /// glyph of the **DDROM** is still a sensible approximation
+        const MAX_DISTANCE: u32 = 8;
+
+        let outliers: heapless::Vec<_, 256> = MAP
+            .entries()
+            .filter(|(_, &glyph)| ddrom::search(glyph).is_none())
+            .filter_map(|(&ch, &glyph)| {
+                let (code, distance) = ddrom::approx(glyph);
+                (distance > MAX_DISTANCE).then_some((ch, code, distance))
+            })
+            .collect();
+        assert!(outliers.is_empty(), "(char, code, distance): {outliers:?}");
+    }
+
+    #[test]
+    fn dilate_and_erode() {
+        let dot = Bitmap::new([0, 0, 0, 0b00100, 0, 0, 0, 0]);
+        let plus = Bitmap::new([0, 0, 0b00100, 0b01110, 0b00100, 0, 0, 0]);
+        assert_eq!(dot.dilate(), plus);
+        assert_eq!(plus.erode(), dot);
+
+        // The pixels outside of the cell are off
+        let corner = Bitmap::new([0b10000, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(corner.dilate().raw(), [0b11000, 0b10000, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(Bitmap::new([0b11111; 8]).erode().raw()[0], 0);
+    }
+
+    #[test]
+    fn pack_round_trip() {
+        for ch in ['A', 'g', '→', '¥'] {
+            let bitmap = Bitmap::render(ch);
+            assert_eq!(Bitmap::unpack(bitmap.pack()), bitmap);
+            assert_eq!(bitmap.pack(), Bitmap::render(ch).pack());
+        }
+        assert_ne!(Bitmap::render('A').pack(), Bitmap::render('B').pack());
+        assert!(Bitmap::new([0b11111; 8]).pack() < 1 << 40);
+    }
+
+    #[test]
+    fn bools_round_trip() {
+        let bitmap = Bitmap::render('&');
+        let bools = bitmap.to_bools();
+        assert_eq!(Bitmap::from_bools(&bools), bitmap);
+        let lit = (0..40u8).filter(|&i| bools[usize::from(i)]);
+        assert!(lit.map(|i| (i % 5, i / 5)).eq(bitmap.pixels()));
+    }
+
+    #[test]
+    fn eq_masked() {
+        let g = Bitmap::render('g');
+        let mut rows = g.raw();
+        rows[7] ^= 0b11111;
+        rows[2] |= 0b100000;
+        let mut mask = [0b11111; 8];
+        mask[7] = 0;
+        assert!(g.eq_masked(Bitmap::new(rows), mask));
+        assert!(!g.eq_masked(Bitmap::new(rows), [0b11111; 8]));
+    }
+
+    #[test]
+    fn from_points_diagonal() {
+        let diagonal = (0..5).map(|i| (i, i)).chain([(5, 5), (0, 8)]);
+        let mut expected = Bitmap::default();
+        (0..5).for_each(|i| expected.set(i, i, true));
+        let bitmap = Bitmap::from_points(diagonal);
+        assert_eq!(bitmap, expected);
+        assert!(bitmap.pixels().eq((0..5).map(|i| (i, i))));
+    }
+
+    #[test]
+    fn row_distances() {
+        let a = Bitmap::render('O');
+        let mut rows = a.raw();
+        rows[5] ^= 0b10100;
+        let b = Bitmap::new(rows);
+        assert_eq!(a.row_distances(b), [0, 0, 0, 0, 0, 2, 0, 0]);
+        assert_eq!(a.row_distances(b).iter().sum::<u32>(), a.distance(b));
+    }
+
+    #[test]
+    fn rotate_cw() {
+        let bar = Bitmap::new([0, 0b11111, 0, 0, 0, 0, 0, 0]);
+        let column = Bitmap::new([0b00010, 0b00010, 0b00010, 0b00010, 0b00010, 0, 0, 0]);
+        assert_eq!(bar.rotate_cw(), column);
+
+        // The rows after the fifth one are clipped
+        let clipped = Bitmap::new([0, 0, 0, 0, 0, 0, 0b11111, 0]);
+        assert_eq!(clipped.rotate_cw(), Bitmap::default());
+    }
+
+    #[test]
+    fn blend_step() {
+        let (from, to) = (Bitmap::render('A'), Bitmap::render('B'));
+        let diff = (from.pack() ^ to.pack()).count_ones();
+        assert_eq!(Bitmap::blend_step(from, to, 0, 4), from);
+        assert_eq!(Bitmap::blend_step(from, to, 4, 4), to);
+        assert_eq!(Bitmap::blend_step(from, to, 9, 4), to);
+        let half = Bitmap::blend_step(from, to, u32::MAX / 2, u32::MAX - 1);
+        assert_eq!((from.pack() ^ half.pack()).count_ones(), diff / 2);
+        for step in 1..4 {
+            let blend = Bitmap::blend_step(from, to, step, 4);
+            assert_eq!((from.pack() ^ blend.pack()).count_ones(), diff * step / 4);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "embedded-graphics")]
+    fn image_raw_round_trip() {
+        let bitmap = Bitmap::render('A');
+        let data = bitmap.to_image_raw_data();
+        let image = ImageRaw::<BinaryColor>::new(&data, 5);
+        assert_eq!(Bitmap::from_image_raw(&image), Some(bitmap));
+        let wide = ImageRaw::<BinaryColor>::new(&data, 8);
+        assert_eq!(Bitmap::from_image_raw(&wide), None);
+    }
+
+    #[test]
+    fn blit_gray_thresholds() {
+        let gradient = from_fn(|y| from_fn(|x| ((y * 5 + x) * 255 / 39) as u8));
+        let lit = |threshold, gamma| {
+            let bitmap = Bitmap::blit_gray(&gradient, threshold, gamma);
+            bitmap.raw().iter().map(|row| row.count_ones()).sum::<u32>()
+        };
+        assert!(lit(64, Gamma::Linear) < lit(128, Gamma::Linear));
+        assert!(lit(128, Gamma::Linear) < lit(192, Gamma::Linear));
+        assert_eq!(lit(128, Gamma::Linear), 20);
+        assert!(lit(128, Gamma::Darken) > lit(128, Gamma::Linear));
+        assert!(lit(128, Gamma::Lighten) < lit(128, Gamma::Linear));
+    }
+
+    #[test]
+    fn outline() {
+        let block = Bitmap::new([0, 0, 0b01110, 0b01110, 0b01110, 0, 0, 0]);
+        let border = [0, 0b01110, 0b10001, 0b10001, 0b10001, 0b01110, 0, 0];
+        assert_eq!(block.outline().raw(), border);
+        // Like with `dilate`, there's nothing outside of the cell
+        assert!(Bitmap::new([0b11111; 8]).outline().is_blank());
+    }
+
+    #[test]
+    fn weights() {
+        let i = Bitmap::render('i');
+        assert_eq!(i.column_weights(), [0, 2, 6, 1, 0]);
+        assert_eq!(i.row_weights(), [1, 0, 2, 1, 1, 1, 3, 0]);
+        assert_eq!(Bitmap::render('M').column_weights(), [7, 1, 2, 1, 7]);
+    }
+}
@@ -1,11 +1,62 @@
-use core::iter::zip;
+use core::array::from_fn;
+use core::cmp::Reverse;
+use core::fmt::Write;
+use core::iter::{repeat, zip};
+use core::ops::Range;
 
+use super::cmd::{Command, Direction, Lines, WRITE_TIME_US};
 use super::ddrom;
-use super::Bitmap;
+use super::{Bitmap, CompositeOp};
+
+mod animation;
+pub use animation::AnimationCache;
+
+mod backlight;
+pub use backlight::Effect;
+
+mod icon;
+pub use icon::BlinkIcon;
+
+mod log;
+pub use log::LogView;
+
+mod marquee;
+pub use marquee::AutoText;
+
+mod odometer;
+pub use odometer::Odometer;
+
+mod policy;
+pub use policy::{CgramPolicy, Fifo, Nearest};
+
+mod present;
+
+mod sprite;
+pub use sprite::{Sprite, SpriteLayer};
+
+mod status_line;
+pub use status_line::StatusLine;
+
+mod viewport;
+pub use viewport::Viewport;
 
 pub type CgRam = heapless::Vec<[u8; 8], 8>;
 pub type DdRam = [u8; 16];
 
+/// A glyph drawn with text, as 8 lines of 5 characters each followed by a newline
+pub type GlyphArt = heapless::String<48>;
+
+/// Returns the **DDRAM** address of the cell `i` of the [`Canvas`]
+///
+/// The 16 cells are driven by the controller as two lines of 8 characters
+pub fn ddram_address(i: usize) -> u8 {
+    if i >= 8 {
+        (i - 8 + 0x40) as u8
+    } else {
+        i as u8
+    }
+}
+
 /// How to handle the gap between each charatecter of the display
 ///
 /// LCD character display usually have a gap of inactive pixels
@@ -23,49 +74,893 @@ pub enum Gap {
     Hide,
 }
 
-#[derive(Default)]
+/// What [`Canvas::write`] does with the text that doesn't fit
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum Overflow {
+    /// Continue from the first cell, over the start of the text
+    #[default]
+    Wrap,
+    /// Drop the characters in excess
+    Truncate,
+    /// Move the text back one cell for each character in excess,
+    /// so that its end is always shown
+    Scroll,
+}
+
+/// What a cell is rendered as when its glyph can't be shown
+///
+/// A glyph can't be shown when it's not in the **DDROM**, all the **CGRAM**
+/// slots are taken and [`Canvas::approx`] is not set. See [`Canvas::set_fallback`]
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum Fallback {
+    /// A blank cell, which can't be told apart from an intentional space
+    #[default]
+    Space,
+    /// A fully lit cell, which makes the missing glyphs stand out
+    Block,
+    /// The given **DDROM** address
+    Code(u8),
+}
+
+impl Fallback {
+    /// Returns the **DDRAM** code the cell is rendered as
+    pub fn code(self) -> u8 {
+        match self {
+            Self::Space => b' ',
+            Self::Block => 0xff,
+            Self::Code(code) => code,
+        }
+    }
+}
+
+/// Line of the [`Canvas`] reserved to show a status
+///
+/// See [`Canvas::status`]
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum StatusRow {
+    Top,
+    Bottom,
+}
+
+/// Distance between the tab stops, when they are not set
+pub const TAB_WIDTH: usize = 4;
+
+/// Maximum number of snapshots kept by the undo stack of a [`Canvas`]
+pub const UNDO_DEPTH: usize = 4;
+
+/// Snapshot of the contents of a [`Canvas`]
+///
+/// See [`Canvas::snapshot`] and [`Canvas::restore`]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct CanvasState {
+    data: [[u8; 8]; 16],
+}
+
+/// Region of cells that blinks in software
+struct Blink {
+    cells: Range<usize>,
+    /// Number of frames the region stays visible, and then hidden
+    period: u32,
+}
+
 pub struct Canvas {
     data: [[u8; 8]; 16],
     gap: Gap,
     pub approx: bool,
+    /// How the cells are split into lines
+    ///
+    /// With [`Lines::One`] all the 16 cells make up a single line,
+    /// while with [`Lines::Two`] each line is made of 8 cells
+    pub lines: Lines,
+    /// Direction in which [`write`](Self::write) advances
+    ///
+    /// With [`Direction::Left`] each line is filled starting from
+    /// its last cell, like with right-to-left scripts
+    pub direction: Direction,
+    /// Line reserved to show a status, like a clock or the battery level
+    ///
+    /// The reserved line is only changed by [`set_status`](Self::set_status),
+    /// while [`write`](Self::write) and the scrolling methods only act on the
+    /// rest of the canvas. It's ignored when there is a single line.
+    pub status: Option<StatusRow>,
+    /// What [`write`](Self::write) does when the text doesn't fit
+    pub overflow: Overflow,
+    /// Cells updated first by [`present_within`](Self::present_within)
+    /// when the whole frame doesn't fit its time budget
+    pub priority: Option<Range<usize>>,
+    /// What the glyphs that can't be shown are rendered as
+    fallback: Fallback,
+    blink: Option<Blink>,
+    /// Cells shown inverted, one bit each starting from the least significant
+    inverted: u16,
+    /// Frame counter advanced by [`tick`](Self::tick)
+    frame: u32,
+    undo: heapless::Vec<CanvasState, UNDO_DEPTH>,
+    /// Cells of each line where a tab stops, in ascending order
+    ///
+    /// When empty there is a stop every [`TAB_WIDTH`] cells
+    tab_stops: heapless::Vec<usize, 16>,
+    /// Last frame sent to the display by [`present_if_changed`](Self::present_if_changed)
+    presented: Option<(DdRam, CgRam)>,
+    /// Glyphs that always take the **CGRAM**, see [`with_fixed_cgram`](Self::with_fixed_cgram)
+    fixed_cgram: Option<[Bitmap; 8]>,
+    /// Animation of the backlight, see [`backlight`](Self::backlight)
+    backlight: Option<Effect>,
+}
+
+impl Default for Canvas {
+    fn default() -> Self {
+        Self {
+            data: [[0; 8]; 16],
+            gap: Gap::default(),
+            approx: false,
+            lines: Lines::One,
+            direction: Direction::Right,
+            status: None,
+            overflow: Overflow::default(),
+            priority: None,
+            fallback: Fallback::default(),
+            blink: None,
+            inverted: 0,
+            frame: 0,
+            undo: heapless::Vec::new(),
+            tab_stops: heapless::Vec::new(),
+            presented: None,
+            fixed_cgram: None,
+            backlight: None,
+        }
+    }
 }
 
 impl Canvas {
+    /// Creates a [`Canvas`] whose **CGRAM** always holds the given glyphs
+    ///
+    /// Each glyph takes the slot of its index, and the slots are never
+    /// reassigned, so the cells with these glyphs always render to the same
+    /// codes and the **CGRAM** never has to be uploaded again. The glyphs
+    /// found neither in the **DDROM** nor here are approximated, if
+    /// [`approx`](Self::approx) is set, or rendered as the [`Fallback`].
+    pub fn with_fixed_cgram(glyphs: [Bitmap; 8]) -> Self {
+        Self {
+            fixed_cgram: Some(glyphs),
+            ..Self::default()
+        }
+    }
+
+    /// Sets what the glyphs that can't be shown are rendered as
+    ///
+    /// During development a [`Fallback::Block`] makes the cells that
+    /// overflow the **CGRAM** obvious, while in production the default
+    /// [`Fallback::Space`] hides them. The fallback is only used when
+    /// [`approx`](Self::approx) is not set.
+    pub fn set_fallback(&mut self, fallback: Fallback) {
+        self.fallback = fallback;
+    }
+
     pub fn render(&self) -> (DdRam, CgRam) {
+        self.render_with(&mut Fifo)
+    }
+
+    /// Renders the [`Canvas`] assigning the **CGRAM** slots with the given policy
+    pub fn render_with(&self, policy: &mut impl CgramPolicy) -> (DdRam, CgRam) {
+        self.render_data(self.data, policy)
+    }
+
+    /// Renders the contents of the [`Canvas`] shifted `px` pixels to the left
+    ///
+    /// The result is the same as calling [`shift_left`](Self::shift_left)
+    /// `px` times before [`render`](Self::render), but the stored data is
+    /// left untouched, so the same content can be shown at any offset
+    /// without having to walk there one pixel at a time.
+    pub fn render_at_offset(&self, px: usize) -> (DdRam, CgRam) {
+        self.render_data(self.shifted(px), &mut Fifo)
+    }
+
+    /// Renders the given cells following the settings of the [`Canvas`]
+    fn render_data(&self, data: [[u8; 8]; 16], policy: &mut impl CgramPolicy) -> (DdRam, CgRam) {
+        let data = self.blinked(self.inverted(data)).map(Bitmap::new);
+
         let mut cgram = CgRam::new();
+        if let Some(glyphs) = self.fixed_cgram {
+            cgram.extend(glyphs.map(Bitmap::raw));
+        }
         let mut ddram = DdRam::default();
-        for (ddram, ch) in zip(&mut ddram, self.data.map(Bitmap::new)) {
-            *ddram = Self::render_char(ch, &mut cgram, self.approx);
+        for i in Self::render_order(&data) {
+            ddram[i] = Self::render_char(data[i], &mut cgram, policy, self.approx, self.fallback);
         }
         (ddram, cgram)
     }
 
+    /// Order in which the cells are rendered
+    ///
+    /// The cells are taken in order, unless their glyphs that are not in the
+    /// **DDROM** are more than the **CGRAM** slots: then the cells whose glyph
+    /// appears more times come first, so that the slots are given to the most
+    /// used glyphs and only the rarer ones are approximated (or rendered as
+    /// the [`Fallback`]).
+    fn render_order(data: &[Bitmap; 16]) -> [usize; 16] {
+        let mut order = from_fn(|i| i);
+        let custom = (0..16)
+            .filter(|&i| ddrom::search(data[i]).is_none() && !data[..i].contains(&data[i]))
+            .count();
+        if custom > CgRam::new().capacity() {
+            let count = |ch| data.iter().filter(|&&c| c == ch).count();
+            order.sort_unstable_by_key(|&i| (Reverse(count(data[i])), i));
+        }
+        order
+    }
+
+    /// Number of **CGRAM** slots needed to show the given text
+    ///
+    /// This counts the different glyphs of the text that are not in the
+    /// **DDROM**, without rendering anything, so it can be used to check
+    /// that some static content fits the 8 slots. The glyphs are the ones
+    /// of [`Bitmap::render`], and the text is assumed to be written with
+    /// [`Gap::Hide`], so that each character takes exactly one cell.
+    pub fn static_glyph_cost(text: &str) -> usize {
+        let custom = |ch| ddrom::search(Bitmap::render(ch)).is_none();
+        let seen = |i, ch| {
+            text.chars()
+                .take(i)
+                .any(|c| Bitmap::render(c) == Bitmap::render(ch))
+        };
+        text.chars()
+            .enumerate()
+            .filter(|&(i, ch)| custom(ch) && !seen(i, ch))
+            .count()
+    }
+
+    /// Number of the **CGRAM** slots used and left free by a render
+    ///
+    /// The `cgram` is the one returned by [`render`](Self::render), where
+    /// the glyph at index `i` is the one shown by the character code `i`
+    pub fn cgram_usage(cgram: &CgRam) -> (usize, usize) {
+        (cgram.len(), cgram.capacity() - cgram.len())
+    }
+
+    /// Draws each glyph placed in the **CGRAM** by a render as text
+    ///
+    /// The lit pixels are drawn as `#` and the others as `:`,
+    /// like the [`Display`](core::fmt::Display) of the lines of a [`Bitmap`]
+    pub fn cgram_art(cgram: &CgRam) -> heapless::Vec<GlyphArt, 8> {
+        let glyph_art = |glyph: &[u8; 8]| {
+            let mut art = GlyphArt::new();
+            for row in glyph {
+                for bit in (0..5).rev() {
+                    // NOTE:
+                    // The capacity fits exactly 8 lines of 5 pixels and a newline
+                    let _ = art.push(if row >> bit & 1 != 0 { '#' } else { ':' });
+                }
+                let _ = art.push('\n');
+            }
+            art
+        };
+        cgram.iter().map(glyph_art).collect()
+    }
+
+    /// Inverts the pixels of the cells set by [`set_cell_invert`](Self::set_cell_invert)
+    fn inverted(&self, mut data: [[u8; 8]; 16]) -> [[u8; 8]; 16] {
+        for (x, cell) in data.iter_mut().enumerate() {
+            if self.inverted >> x & 1 != 0 {
+                cell.iter_mut().for_each(|row| *row ^= 0b11111);
+            }
+        }
+        data
+    }
+
+    /// Blanks the cells of the blinking region, when it's hidden
+    fn blinked(&self, mut data: [[u8; 8]; 16]) -> [[u8; 8]; 16] {
+        if let Some(blink) = &self.blink {
+            if self.frame / blink.period % 2 == 1 {
+                data[blink.cells.clone()].fill([0; 8]);
+            }
+        }
+        data
+    }
+
+    /// Returns a copy of the data of the [`Canvas`] shifted `px` pixels to the left
+    ///
+    /// The cells are seen as a ring of pixel columns, each cell being
+    /// 5 pixels wide, or 6 when [`Gap::Hide`] is used
+    fn shifted(&self, px: usize) -> [[u8; 8]; 16] {
+        let body = self.body();
+        let width = 5 + usize::from(self.gap == Gap::Hide);
+        let len = width * body.len();
+
+        let mut data = self.data;
+        data[body.clone()].fill([0; 8]);
+        for x in 0..len {
+            let src = (x + px) % len;
+            let (src_cell, src_bit) = (body.start + src / width, width - 1 - src % width);
+            let (dst_cell, dst_bit) = (body.start + x / width, width - 1 - x % width);
+            for (dst, src) in zip(&mut data[dst_cell], self.data[src_cell]) {
+                *dst |= (src >> src_bit & 1) << dst_bit;
+            }
+        }
+        data
+    }
+
+    /// Estimates the time in µs needed to update the display from `old` to `new`
+    ///
+    /// The estimate counts the same writes done by [`Driver::present_frame`](super::Driver::present_frame):
+    /// the 8 rows of each changed **CGRAM** slot and each changed **DDRAM**
+    /// cell, plus an address command at the start of each run of contiguous
+    /// slots or cells, using the timings of the datasheet.
+    /// Nothing is sent to the display.
+    pub fn measure_render_cost(old: &(DdRam, CgRam), new: &(DdRam, CgRam)) -> u32 {
+        let cgram = new
+            .1
+            .iter()
+            .enumerate()
+            .filter(|&(slot, glyph)| old.1.get(slot) != Some(glyph))
+            .flat_map(|(slot, _)| slot * 8..slot * 8 + 8);
+
+        let ddram = zip(old.0, new.0)
+            .enumerate()
+            .filter(|(_, (old, new))| old != new)
+            .map(|(i, _)| usize::from(ddram_address(i)));
+
+        Self::runs_cost(cgram) + Self::runs_cost(ddram)
+    }
+
+    /// Estimates the time in µs needed to write to the given addresses
+    fn runs_cost(addresses: impl Iterator<Item = usize>) -> u32 {
+        let address_time = Command::DdRamAddress(0).exec_time_us();
+        let mut next = None;
+        addresses.fold(0, |cost, address| {
+            let jump = next != Some(address);
+            next = Some(address + 1);
+            cost + u32::from(jump) * address_time + WRITE_TIME_US
+        })
+    }
+
     /// Renders a character from the [`Canvas`]
-    fn render_char(ch: Bitmap, cgram: &mut CgRam, approx: bool) -> u8 {
+    ///
+    /// The returned code is either an address of the **DDROM** or, when it's
+    /// one of the [`CGRAM_CODES`](ddrom::CGRAM_CODES), the index of a glyph
+    /// in `cgram`: [`ddrom::search`] never returns these codes, so
+    /// the two can't be confused.
+    ///
+    /// The glyphs already in `cgram` are looked up before asking the policy,
+    /// so identical cells always share a single slot, whatever the policy
+    /// and the [`approx`](Self::approx) setting. The glyphs that are neither
+    /// found nor approximated are rendered as the `fallback`.
+    fn render_char(
+        ch: Bitmap,
+        cgram: &mut CgRam,
+        policy: &mut impl CgramPolicy,
+        approx: bool,
+        fallback: Fallback,
+    ) -> u8 {
+        // NOTE:
+        // `ch` was built with `Bitmap::new`, so the hidden pixel of `Gap::Hide`
+        // is already masked away and can't make two equal glyphs differ
         let raw = ch.raw();
         ddrom::search(ch)
             .or_else(|| Some(cgram.iter().position(|&c| c == raw)? as u8))
-            .or_else(|| cgram.push(raw).map(|_| cgram.len() as u8 - 1).ok())
-            .unwrap_or_else(|| approx.then(|| ddrom::approx(ch).0).unwrap_or(b' '))
+            .or_else(|| policy.allocate(ch, cgram))
+            .unwrap_or_else(|| {
+                if approx {
+                    ddrom::approx(ch).0
+                } else {
+                    fallback.code()
+                }
+            })
+    }
+
+    /// Combines the cells of another [`Canvas`] into this one
+    ///
+    /// This allows to keep, for example, a static background and a moving
+    /// sprite on two different layers, and composite them every frame.
+    /// Only the cells that are not blank in `other` are touched, so with
+    /// [`CompositeOp::Replace`] the blank cells don't cover the background.
+    pub fn overlay(&mut self, other: &Canvas, op: CompositeOp) {
+        Self::composite(&mut self.data, other.data, op);
+    }
+
+    /// Combines the cells of `above` into `below`, skipping the blank ones
+    fn composite(below: &mut [[u8; 8]; 16], above: [[u8; 8]; 16], op: CompositeOp) {
+        for (cell, above) in zip(below, above) {
+            if above == [0; 8] {
+                continue;
+            }
+            zip(cell, above).for_each(|(below, above)| *below = op.apply(*below, above));
+        }
+    }
+
+    /// Renders the [`Canvas`] with another one on top, each scrolled by its own offset
+    ///
+    /// The two canvases are shifted like with [`render_at_offset`](Self::render_at_offset),
+    /// each following its own [`Gap`], and then combined like with
+    /// [`overlay`](Self::overlay). This allows, for example, a slow background
+    /// pattern to scroll behind a fast ticker, without touching either of them.
+    pub fn render_layers(
+        &self,
+        px: usize,
+        top: &Canvas,
+        top_px: usize,
+        op: CompositeOp,
+    ) -> (DdRam, CgRam) {
+        let mut data = self.shifted(px);
+        Self::composite(&mut data, top.shifted(top_px), op);
+        self.render_data(data, &mut Fifo)
+    }
+
+    /// Erodes every cell of the [`Canvas`] by one pixel
+    ///
+    /// Calling this once per frame makes the contents dissolve, until the
+    /// canvas is blank (see [`Bitmap::erode`]).
+    /// Returns `false` once there is nothing left to fade.
+    pub fn fade_out_step(&mut self) -> bool {
+        for cell in &mut self.data {
+            *cell = Bitmap::new(*cell).erode().raw();
+        }
+        self.data.iter().flatten().any(|&v| v != 0)
+    }
+
+    /// Takes a snapshot of the contents of the [`Canvas`]
+    pub fn snapshot(&self) -> CanvasState {
+        CanvasState { data: self.data }
+    }
+
+    /// Restores the contents of the [`Canvas`] from a snapshot
+    pub fn restore(&mut self, state: CanvasState) {
+        self.data = state.data;
+    }
+
+    /// Pushes a snapshot of the current contents on the undo stack
+    ///
+    /// The stack keeps at most [`UNDO_DEPTH`] snapshots,
+    /// when it's full the oldest one is dropped to make room.
+    /// The editing operations, like [`insert_char`](Self::insert_char)
+    /// and [`delete_char`](Self::delete_char), call this on their own.
+    pub fn push_undo(&mut self) {
+        if self.undo.is_full() {
+            self.undo.remove(0);
+        }
+        // NOTE:
+        // A slot was just freed if the stack was full, so this can't fail
+        let _ = self.undo.push(self.snapshot());
+    }
+
+    /// Restores the contents of the last snapshot pushed on the undo stack
+    ///
+    /// Returns `false` if there was nothing to undo
+    pub fn undo(&mut self) -> bool {
+        let Some(state) = self.undo.pop() else {
+            return false;
+        };
+        self.restore(state);
+        true
+    }
+
+    /// Advances the frame counter of the [`Canvas`]
+    ///
+    /// This should be called once per frame, and drives the time based
+    /// effects like [`blink_region`](Self::blink_region) and the
+    /// [backlight animation](Self::set_backlight_animation)
+    pub fn tick(&mut self) {
+        self.frame = self.frame.wrapping_add(1);
+    }
+
+    /// Adds a shadow to the glyphs of the given cells
+    ///
+    /// Each glyph is combined with a copy of itself moved one pixel down and
+    /// to the right, giving an embossed look that suits titles. The shadow is
+    /// clipped at the edges of each cell, so it never falls in the gap, and
+    /// each modified glyph usually takes a **CGRAM** slot.
+    pub fn emboss(&mut self, start: usize, len: usize) {
+        let end = (start + len).min(self.data.len());
+        for cell in &mut self.data[start.min(end)..end] {
+            let glyph = Bitmap::new(*cell);
+            *cell = glyph.composite(glyph.shift(1, 1), CompositeOp::Or).raw();
+        }
+    }
+
+    /// Sets whether the given cell is shown inverted
+    ///
+    /// Like the blinking, the inversion only happens at render time, so the
+    /// contents of the cell are preserved and it stays inverted when they
+    /// change, which suits persistent highlights like the selected entry of
+    /// a menu. Each inverted cell usually takes a **CGRAM** slot.
+    pub fn set_cell_invert(&mut self, x: usize, invert: bool) {
+        if x >= self.data.len() {
+            return;
+        }
+        if invert {
+            self.inverted |= 1 << x;
+        } else {
+            self.inverted &= !(1 << x);
+        }
+    }
+
+    /// Makes the given cells blink
+    ///
+    /// The cells alternate between being shown and being blank every
+    /// `period_frames` frames (see [`tick`](Self::tick)).
+    /// The blinking only happens at render time, so the contents of the
+    /// cells are preserved. Only one region can blink at a time, so this
+    /// replaces the previous one.
+    pub fn blink_region(&mut self, start: usize, len: usize, period_frames: u32) {
+        let end = (start + len).min(self.data.len());
+        self.blink = Some(Blink {
+            cells: start.min(end)..end,
+            period: period_frames.max(1),
+        });
+    }
+
+    /// Stops the blinking started by [`blink_region`](Self::blink_region)
+    pub fn stop_blink(&mut self) {
+        self.blink = None;
+    }
+
+    /// Number of cells that make up a line of the [`Canvas`]
+    pub fn line_width(&self) -> usize {
+        match self.lines {
+            Lines::One => self.data.len(),
+            Lines::Two => self.data.len() / 2,
+        }
+    }
+
+    /// Number of lines of the [`Canvas`]
+    pub fn line_count(&self) -> usize {
+        self.data.len() / self.line_width()
+    }
+
+    /// Range of the cells not reserved by the [`status`](Self::status) line
+    fn body(&self) -> Range<usize> {
+        let width = self.line_width();
+        match (self.lines, self.status) {
+            (Lines::Two, Some(StatusRow::Top)) => width..self.data.len(),
+            (Lines::Two, Some(StatusRow::Bottom)) => 0..self.data.len() - width,
+            _ => 0..self.data.len(),
+        }
+    }
+
+    /// Replaces the contents of the [`status`](Self::status) line with the text
+    ///
+    /// The text is placed like with [`write_line`](Self::write_line).
+    /// Nothing is written if there is no status line.
+    pub fn set_status(&mut self, text: &str) {
+        let line = match (self.lines, self.status) {
+            (Lines::Two, Some(StatusRow::Top)) => 0,
+            (Lines::Two, Some(StatusRow::Bottom)) => self.line_count() - 1,
+            _ => return,
+        };
+        self.fill_line(line, text.chars());
+    }
+
+    /// Range of the cells of the line that contains the cell `x`
+    fn line_of(&self, x: usize) -> Range<usize> {
+        let start = x - x % self.line_width();
+        start..start + self.line_width()
+    }
+
+    /// Inserts a character at the cell `x`
+    ///
+    /// The cells that follow on the same line, in the
+    /// [`direction`](Self::direction) of the canvas, are moved one place
+    /// further and the last one of the line is dropped.
+    /// Like the other cells outside of the canvas, the ones of the
+    /// [`status`](Self::status) line are ignored, leaving everything untouched.
+    pub fn insert_char(&mut self, x: usize, ch: char) {
+        let Some(following) = self.following(x) else {
+            return;
+        };
+        self.push_undo();
+        match self.direction {
+            Direction::Right => self.data[following].rotate_right(1),
+            Direction::Left => self.data[following].rotate_left(1),
+        }
+        self.data[x] = Bitmap::render(ch).raw();
+    }
+
+    /// Deletes the character at the cell `x`
+    ///
+    /// The cells that follow on the same line, in the
+    /// [`direction`](Self::direction) of the canvas, are moved one place
+    /// back and the last one of the line is left blank.
+    /// Like the other cells outside of the canvas, the ones of the
+    /// [`status`](Self::status) line are ignored, leaving everything untouched.
+    pub fn delete_char(&mut self, x: usize) {
+        let Some(following) = self.following(x) else {
+            return;
+        };
+        self.push_undo();
+        let last = match self.direction {
+            Direction::Right => {
+                self.data[following.clone()].rotate_left(1);
+                following.end - 1
+            }
+            Direction::Left => {
+                self.data[following.clone()].rotate_right(1);
+                following.start
+            }
+        };
+        self.data[last] = [0; 8];
+    }
+
+    /// Range of the cells from `x` to the end of its line, following the
+    /// [`direction`](Self::direction) of the canvas
+    ///
+    /// Returns [`None`] if `x` is not one of the cells of the [`body`](Self::body)
+    fn following(&self, x: usize) -> Option<Range<usize>> {
+        if !self.body().contains(&x) {
+            return None;
+        }
+        let line = self.line_of(x);
+        Some(match self.direction {
+            Direction::Right => x..line.end,
+            Direction::Left => line.start..x + 1,
+        })
+    }
+
+    /// Replaces the contents of the given line with the text
+    ///
+    /// The text is truncated if it doesn't fit the line, or padded with blank
+    /// cells otherwise. The characters are placed following the
+    /// [`direction`](Self::direction) of the canvas.
+    /// Nothing is written if the line is outside of the canvas.
+    pub fn write_line(&mut self, line: usize, text: &str) {
+        self.fill_line(line, text.chars());
+    }
+
+    /// Writes a number with the given decimals followed by its unit, like `"23.4C"`
+    ///
+    /// The text starts at the cell `x` and can take the rest of its line,
+    /// following the [`direction`](Self::direction) of the canvas.
+    /// When it doesn't fit the decimals are dropped, one at a time, and if
+    /// that's not enough the cells are filled with `#` to signal the overflow.
+    /// Returns the number of cells written, which is `0` when `x` is not one
+    /// of the cells of the [`body`](Self::body), as the others are ignored.
+    pub fn value(&mut self, x: usize, value: f32, decimals: u8, unit: &str) -> usize {
+        let Some(cells) = self.following(x) else {
+            return 0;
+        };
+        let width = cells.len();
+
+        let mut text = heapless::String::<32>::new();
+        let fits = (0..=usize::from(decimals)).rev().any(|decimals| {
+            text.clear();
+            // NOTE:
+            // A formatting error means the text doesn't fit the string either
+            let formatted = write!(text, "{value:.decimals$}{unit}").is_ok();
+            formatted && text.chars().count() <= width
+        });
+        if !fits {
+            text.clear();
+            // NOTE:
+            // A line is at most 16 cells, so this can't fail
+            repeat('#').take(width).for_each(|ch| {
+                let _ = text.push(ch);
+            });
+        }
+
+        let render = |(cell, ch): (&mut [u8; 8], char)| *cell = Bitmap::render(ch).raw();
+        match self.direction {
+            Direction::Right => zip(&mut self.data[cells], text.chars()).for_each(render),
+            Direction::Left => {
+                zip(self.data[cells].iter_mut().rev(), text.chars()).for_each(render)
+            }
+        }
+        text.chars().count()
+    }
+
+    /// Writes a centered banner on the given line
+    ///
+    /// The text is surrounded by a space on each side, when there's room
+    /// for them, and the rest of the line is filled with the `fill` character,
+    /// producing headers like `"== Title =="`.
+    /// When the padding can't be split evenly the extra character goes on the
+    /// right side, and if the text doesn't fit the line it's truncated.
+    pub fn banner(&mut self, line: usize, text: &str, fill: char) {
+        let width = self.line_width();
+        let len = text.chars().count().min(width);
+        let spaced = len + 2 <= width;
+        let pad = width - len - if spaced { 2 } else { 0 };
+
+        let space = spaced.then_some(' ');
+        let chars = repeat(fill)
+            .take(pad / 2)
+            .chain(space)
+            .chain(text.chars().take(len))
+            .chain(space)
+            .chain(repeat(fill));
+        self.fill_line(line, chars);
+    }
+
+    /// Replaces the contents of the given line with the characters
+    ///
+    /// The characters in excess are ignored, while the missing
+    /// ones are replaced by blank cells
+    fn fill_line(&mut self, line: usize, chars: impl Iterator<Item = char>) {
+        self.fill_cells(line, 0..self.line_width(), chars);
+    }
+
+    /// Replaces the contents of the given cells of a line with the characters
+    ///
+    /// The cells are counted from the start of the line, following the
+    /// [`direction`](Self::direction) of the canvas. The characters in excess
+    /// are ignored, while the missing ones are replaced by blank cells.
+    /// Like the other cells outside of the canvas, the lines after the last
+    /// one are ignored, leaving everything untouched.
+    fn fill_cells(
+        &mut self,
+        line: usize,
+        range: Range<usize>,
+        mut chars: impl Iterator<Item = char>,
+    ) {
+        if line >= self.line_count() {
+            return;
+        }
+        let cells = self.line_of(line * self.line_width());
+        for i in range {
+            let x = match self.direction {
+                Direction::Right => cells.start + i,
+                Direction::Left => cells.end - 1 - i,
+            };
+            self.data[x] = chars.next().map_or([0; 8], |ch| Bitmap::render(ch).raw());
+        }
     }
 
     /// Write the given text onto the canvas
     ///
-    /// If the text doesn't fit inside the drawing area
-    /// it will wrap around,
+    /// The characters are placed following the [`direction`](Self::direction)
+    /// of the canvas, and if the text doesn't fit inside the drawing area
+    /// it's handled as set by [`overflow`](Self::overflow). A tab (`'\t'`) moves to the next tab stop
+    /// (see [`set_tab_stops`](Self::set_tab_stops)).
     pub fn write(&mut self, text: &str, custom_gap: Option<Gap>) {
-        for (i, s) in text.chars().enumerate() {
+        let mut i = 0;
+        for s in text.chars() {
             // NOTE:
-            // When `Gap::Skip` the space between the characters has to be placed
-            // manually
-            if let Gap::Skip = custom_gap.unwrap_or(self.gap) {
-                self.shift_left(Some(Gap::Skip));
+            // A tab is written as the blank cells that separate it from the next stop
+            let (s, count) = match s {
+                '\t' => (' ', self.tab_width(i)),
+                s => (s, 1),
+            };
+            for _ in 0..count {
+                if !self.put_text(&mut i, Bitmap::render(s).raw(), custom_gap) {
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Write the given **DDROM** codes onto the canvas
+    ///
+    /// This reaches the glyphs of the **DDROM** that no [`char`] renders to,
+    /// like the katakana, and they are shown without using the **CGRAM**.
+    /// The codes are placed like the characters of [`write`](Self::write)
+    /// with [`Gap::Hide`], so that each one takes exactly one cell, and the
+    /// ones without a glyph of the **DDROM** (like the ones of the **CGRAM**)
+    /// leave the cell blank.
+    pub fn write_raw(&mut self, codes: &[u8]) {
+        let mut i = 0;
+        for &code in codes {
+            let glyph = ddrom::glyph(code).map_or([0; 8], Bitmap::raw);
+            if !self.put_text(&mut i, glyph, Some(Gap::Hide)) {
+                return;
+            }
+        }
+    }
+
+    /// Places the glyph at the `i`-th cell written by [`write`](Self::write),
+    /// and advances `i`
+    ///
+    /// Returns `false` if the glyph doesn't fit and is dropped
+    fn put_text(&mut self, i: &mut usize, glyph: [u8; 8], custom_gap: Option<Gap>) -> bool {
+        if *i >= self.body().len() {
+            match self.overflow {
+                Overflow::Wrap => {}
+                Overflow::Truncate => return false,
+                Overflow::Scroll => {
+                    self.scroll_text();
+                    *i -= 1;
+                }
+            }
+        }
+        // NOTE:
+        // When `Gap::Skip` the space between the characters has to be placed
+        // manually
+        if let Gap::Skip = custom_gap.unwrap_or(self.gap) {
+            self.shift_left(Some(Gap::Skip));
+        }
+        let x = self.text_cell(*i);
+        self.data[x] = glyph;
+        *i += 1;
+        true
+    }
+
+    /// Moves the text back by one cell, dropping its first character
+    ///
+    /// The cells are taken in the same order of [`write`](Self::write),
+    /// and the last one is left blank
+    fn scroll_text(&mut self) {
+        let len = self.body().len();
+        for i in 1..len {
+            self.data[self.text_cell(i - 1)] = self.data[self.text_cell(i)];
+        }
+        self.data[self.text_cell(len - 1)] = [0; 8];
+    }
+
+    /// Sets the cells of each line where a tab (`'\t'`) written by
+    /// [`write`](Self::write) stops
+    ///
+    /// A tab after the last stop goes to the start of the next line.
+    /// The stops outside of the line are ignored, and with no stops
+    /// there is one every [`TAB_WIDTH`] cells.
+    pub fn set_tab_stops(&mut self, stops: &[usize]) {
+        self.tab_stops.clear();
+        for &stop in stops {
+            if stop < 16 && !self.tab_stops.contains(&stop) {
+                // NOTE:
+                // The distinct stops inside a line are at most 16, so this can't fail
+                let _ = self.tab_stops.push(stop);
+            }
+        }
+        self.tab_stops.sort_unstable();
+    }
+
+    /// Number of cells a tab advances when it's the `i`-th character of a text
+    fn tab_width(&self, i: usize) -> usize {
+        let width = self.line_width();
+        let column = i % width;
+        let next = if self.tab_stops.is_empty() {
+            (column / TAB_WIDTH + 1) * TAB_WIDTH
+        } else {
+            let mut stops = self.tab_stops.iter().copied();
+            stops.find(|&stop| stop > column).unwrap_or(width)
+        };
+        next.min(width) - column
+    }
+
+    /// Returns the cell where the `i`-th character of a text is placed
+    ///
+    /// The text wraps around after the last line of the body, and with
+    /// [`Direction::Left`] the cells of each line are taken in reverse
+    fn text_cell(&self, i: usize) -> usize {
+        let body = self.body();
+        let i = body.start + i % body.len();
+        match self.direction {
+            Direction::Right => i,
+            Direction::Left => {
+                let width = self.line_width();
+                i - i % width + (width - 1 - i % width)
+            }
+        }
+    }
+
+    /// Scrolls the contents of the [`Canvas`] vertically by `px` pixels
+    ///
+    /// The lines are stacked one on top of the other to form a single field
+    /// of pixel rows, so the content moves from one line to the other as if
+    /// there was no gap between them. Positive values scroll up, negative
+    /// ones scroll down, and the rows that go out on one side come back in
+    /// on the other. The [`status`](Self::status) line is left untouched.
+    pub fn vscroll(&mut self, px: i8) {
+        let body = self.body();
+        let width = self.line_width();
+        let rows = 8 * body.len() / width;
+        let px = (px as isize).rem_euclid(rows as isize) as usize;
+        if px == 0 {
+            return;
+        }
+
+        let data = self.data;
+        for row in 0..rows {
+            let src = (row + px) % rows;
+            let (dst_line, src_line) = (body.start + row / 8 * width, body.start + src / 8 * width);
+            for x in 0..width {
+                self.data[dst_line + x][row % 8] = data[src_line + x][src % 8];
             }
-            self.data[i % self.data.len()] = Bitmap::render(s).raw();
         }
     }
 
     /// Shift the contents of the [`Canvas`] one pixel to the left
+    ///
+    /// The pixels that go out of the first cell come back in the last one,
+    /// so after shifting as many times as the pixels of a line the contents
+    /// are back where they started.
     pub fn shift_left(&mut self, custom_gap: Option<Gap>) {
         // NOTE:
         // When `Gap::Hide` use the 6th least significan bit to store
@@ -73,13 +968,546 @@ impl Canvas {
         let shift = 5 + u8::from(custom_gap.unwrap_or(self.gap) == Gap::Hide);
         let mask = (1 << shift) - 1;
 
-        self.data.iter_mut().flatten().for_each(|v| *v <<= 1);
-        for x in 0usize..16 {
+        let body = self.body();
+        self.data[body.clone()]
+            .iter_mut()
+            .flatten()
+            .for_each(|v| *v <<= 1);
+        // NOTE:
+        // All the cells are shifted before moving the carries, and a carry only
+        // lands on the lowest bit of the previous cell, which is never carried,
+        // so the order in which the cells are visited doesn't matter, not even
+        // for the first one, whose carry goes to the last cell
+        for x in body.clone() {
             for y in 0..8 {
-                let prev = x.checked_sub(1).unwrap_or(15);
+                let prev = if x == body.start { body.end } else { x } - 1;
                 self.data[prev][y] |= self.data[x][y] >> shift;
                 self.data[x][y] &= mask;
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Contents of the cells showing the given text, one character each
+    fn cells(text: &str) -> [[u8; 8]; 16] {
+        let mut chars = text.chars();
+        from_fn(|_| chars.next().map_or([0; 8], |ch| Bitmap::render(ch).raw()))
+    }
+
+    /// The `k`-th of a set of distinct glyphs that are not in the **DDROM**
+    fn glyph(k: u8) -> [u8; 8] {
+        from_fn(|y| (y as u8 * 3 + k * 7) % 31 + 1)
+    }
+
+    #[test]
+    fn insert_and_delete_char() {
+        let mut canvas = Canvas {
+            lines: Lines::Two,
+            ..Canvas::default()
+        };
+        canvas.write_line(0, "abc");
+        canvas.insert_char(1, 'x');
+        assert_eq!(canvas.data, cells("axbc"));
+        canvas.delete_char(1);
+        assert_eq!(canvas.data, cells("abc"));
+
+        // The last cell of the line is dropped, the next line is untouched
+        canvas.write_line(0, "abcdefgh");
+        canvas.write_line(1, "i");
+        canvas.insert_char(0, 'x');
+        assert_eq!(canvas.data, cells("xabcdefgi"));
+    }
+
+    #[test]
+    fn fade_out_a_solid_block() {
+        let mut canvas = Canvas {
+            lines: Lines::Two,
+            ..Canvas::default()
+        };
+        canvas.data[3] = [0b11111; 8];
+        let lit = |canvas: &Canvas| {
+            canvas
+                .data
+                .iter()
+                .flatten()
+                .map(|v| v.count_ones())
+                .sum::<u32>()
+        };
+        let mut last = lit(&canvas);
+        let mut steps = 0;
+        while canvas.fade_out_step() {
+            assert!(lit(&canvas) < last);
+            last = lit(&canvas);
+            steps += 1;
+            assert!(steps < 8);
+        }
+        assert_eq!(canvas.data, [[0; 8]; 16]);
+    }
+
+    #[test]
+    fn overflow() {
+        let expected = [
+            (Overflow::Wrap, "qrcdefghijklmnop"),
+            (Overflow::Truncate, "abcdefghijklmnop"),
+            (Overflow::Scroll, "cdefghijklmnopqr"),
+        ];
+        for (overflow, text) in expected {
+            let mut canvas = Canvas {
+                lines: Lines::Two,
+                overflow,
+                ..Canvas::default()
+            };
+            canvas.write("abcdefghijklmnopqr", Some(Gap::Hide));
+            assert_eq!(canvas.data, cells(text), "{overflow:?}");
+        }
+    }
+
+    #[test]
+    fn tab_stops() {
+        let mut canvas = Canvas {
+            lines: Lines::Two,
+            ..Canvas::default()
+        };
+        canvas.write("a\tb", Some(Gap::Hide));
+        assert_eq!(canvas.data, cells("a   b"));
+
+        // After the last stop a tab goes to the next line
+        canvas.set_tab_stops(&[6, 3, 20]);
+        canvas.write("a\tb\tc\td", Some(Gap::Hide));
+        assert_eq!(canvas.data, cells("a  b  c d"));
+    }
+
+    #[test]
+    fn vscroll_across_the_lines() {
+        let mut canvas = Canvas {
+            lines: Lines::Two,
+            ..Canvas::default()
+        };
+        canvas.write_line(1, "ab");
+        canvas.vscroll(8);
+        assert_eq!(canvas.data, cells("ab"));
+
+        // The rows that go out of the top come back in at the bottom
+        canvas.vscroll(3);
+        let a = Bitmap::render('a').raw();
+        assert_eq!(canvas.data[0][..5], a[3..]);
+        assert_eq!(canvas.data[8][5..], a[..3]);
+        canvas.vscroll(-11);
+        assert_eq!(canvas.data, cells("        ab"));
+    }
+
+    #[test]
+    fn write_to_the_left() {
+        let mut canvas = Canvas {
+            lines: Lines::Two,
+            direction: Direction::Left,
+            ..Canvas::default()
+        };
+        canvas.write("123", Some(Gap::Hide));
+        assert_eq!(canvas.data, cells("     321"));
+
+        // Each line is filled from its right end
+        canvas.write("abcdefghi", Some(Gap::Hide));
+        assert_eq!(canvas.data, cells("hgfedcba       i"));
+    }
+
+    #[test]
+    fn insert_and_delete_char_to_the_left() {
+        let left = || Canvas {
+            lines: Lines::Two,
+            direction: Direction::Left,
+            ..Canvas::default()
+        };
+        let (mut canvas, mut expected) = (left(), left());
+        canvas.write_line(0, "abc");
+        canvas.insert_char(6, 'x');
+        expected.write_line(0, "axbc");
+        assert_eq!(canvas.data, expected.data);
+        canvas.delete_char(6);
+        expected.write_line(0, "abc");
+        assert_eq!(canvas.data, expected.data);
+    }
+
+    #[test]
+    fn value() {
+        let mut canvas = Canvas {
+            lines: Lines::Two,
+            ..Canvas::default()
+        };
+        assert_eq!(canvas.value(1, 23.456, 1, "C"), 5);
+        assert_eq!(canvas.data, cells(" 23.5C"));
+
+        // The decimals are dropped, and then the cells are filled with `#`
+        assert_eq!(canvas.value(4, -23.456, 2, "C"), 4);
+        assert_eq!(canvas.data, cells(" 23.-23C"));
+        assert_eq!(canvas.value(6, 123.0, 0, "C"), 2);
+        assert_eq!(canvas.data, cells(" 23.-2##"));
+
+        assert_eq!(canvas.value(16, 1.0, 0, ""), 0);
+        assert_eq!(canvas.data, cells(" 23.-2##"));
+    }
+
+    #[test]
+    fn value_in_the_body_to_the_left() {
+        let mut canvas = Canvas {
+            lines: Lines::Two,
+            direction: Direction::Left,
+            status: Some(StatusRow::Top),
+            ..Canvas::default()
+        };
+        canvas.set_status("abc");
+        assert_eq!(canvas.value(2, 1.5, 1, "V"), 0);
+        assert_eq!(canvas.data, cells("     cba"));
+
+        // The text goes from the cell `x` towards the start of the line
+        assert_eq!(canvas.value(12, 1.5, 1, "V"), 4);
+        assert_eq!(canvas.data, cells("     cba V5.1"));
+        assert_eq!(canvas.value(10, 1.5, 1, "V"), 2);
+        assert_eq!(canvas.data, cells("     cba V2.1"));
+    }
+
+    #[test]
+    fn banner() {
+        let mut canvas = Canvas {
+            lines: Lines::Two,
+            ..Canvas::default()
+        };
+        canvas.banner(0, "Hi", '=');
+        canvas.banner(1, "Odd", '-');
+        assert_eq!(canvas.data, cells("== Hi ==- Odd --"));
+
+        canvas.banner(0, "Too long", '=');
+        assert_eq!(canvas.data, cells("Too long- Odd --"));
+    }
+
+    #[test]
+    fn write_line_outside_of_the_canvas() {
+        let mut canvas = Canvas {
+            lines: Lines::Two,
+            ..Canvas::default()
+        };
+        canvas.write_line(0, "Hi");
+        let before = canvas.data;
+        canvas.write_line(2, "Ignored");
+        canvas.banner(5, "Ignored", '=');
+        assert_eq!(canvas.data, before);
+
+        canvas.lines = Lines::One;
+        canvas.write_line(1, "Ignored");
+        assert_eq!(canvas.data, before);
+    }
+
+    /// A single pixel goes around all the pixels of the canvas, gaps included
+    /// when they are hidden, and it's back in place only at the end
+    #[test]
+    fn shift_left_wraps_a_single_pixel() {
+        for (gap, width) in [(Gap::Skip, 5), (Gap::Hide, 6)] {
+            let mut canvas = Canvas::default();
+            canvas.data[0][3] = 1 << (width - 1);
+            let start = canvas.data;
+            for step in 1..=16 * width {
+                canvas.shift_left(Some(gap));
+                let lit: u32 = canvas.data.iter().flatten().map(|v| v.count_ones()).sum();
+                assert_eq!(lit, 1, "step {step}");
+                assert_eq!(canvas.data == start, step == 16 * width, "step {step}");
+            }
+        }
+    }
+
+    #[test]
+    fn render_at_offset_matches_shift_left() {
+        let mut canvas = Canvas::default();
+        canvas.write("Hello World!", None);
+        let mut shifted = Canvas::default();
+        shifted.write("Hello World!", None);
+        assert_eq!(canvas.render_at_offset(0), shifted.render());
+        for _ in 0..5 {
+            shifted.shift_left(None);
+        }
+        assert_eq!(canvas.render_at_offset(5), shifted.render());
+    }
+
+    /// Identical cells share a single slot, even when one of them has the
+    /// hidden pixel of [`Gap::Hide`] set
+    #[test]
+    fn identical_cells_share_a_cgram_slot() {
+        let heart = [0, 0b01010, 0b11111, 0b11111, 0b01110, 0b00100, 0, 0];
+        for approx in [false, true] {
+            let mut canvas = Canvas {
+                approx,
+                ..Canvas::default()
+            };
+            canvas.data[..8].fill(heart);
+            canvas.data[7][2] |= 0b100000;
+            let (ddram, cgram) = canvas.render();
+            assert_eq!(cgram[..], [heart]);
+            assert_eq!(ddram[..8], [0; 8]);
+        }
+    }
+
+    #[test]
+    fn cgram_usage() {
+        let mut canvas = Canvas::default();
+        for (i, row) in [0b10101, 0b01010, 0b10001].into_iter().enumerate() {
+            canvas.data[i * 2] = [row; 8];
+            canvas.data[i * 2 + 1] = [row; 8];
+        }
+        let (_, cgram) = canvas.render();
+        assert_eq!(Canvas::cgram_usage(&cgram), (3, 5));
+        let art = "#:::#\n#:::#\n#:::#\n#:::#\n#:::#\n#:::#\n#:::#\n#:::#\n";
+        assert_eq!(Canvas::cgram_art(&cgram)[2].as_str(), art);
+    }
+
+    #[test]
+    fn cgram_goes_to_the_most_used_glyphs() {
+        let mut canvas = Canvas::default();
+        // The glyphs used once come first, and would take the slots if they
+        // were given in order
+        for (cell, k) in canvas.data.iter_mut().zip((6..10).chain(0..6).chain(0..6)) {
+            *cell = glyph(k);
+        }
+        let (ddram, cgram) = canvas.render();
+        assert_eq!(cgram.len(), 8);
+        for k in 0..8 {
+            assert!(cgram.contains(&glyph(k)), "glyph {k}");
+        }
+        assert!(ddram[2..4].iter().all(|&code| code >= 8));
+    }
+
+    #[test]
+    fn fallback() {
+        let mut canvas = Canvas::default();
+        for k in 0..9 {
+            canvas.data[usize::from(k)] = glyph(k);
+        }
+        // The ninth glyph doesn't fit the CGRAM
+        let expected = [
+            (Fallback::Space, b' '),
+            (Fallback::Block, 0xff),
+            (Fallback::Code(b'?'), b'?'),
+        ];
+        for (fallback, code) in expected {
+            canvas.set_fallback(fallback);
+            let (ddram, _) = canvas.render();
+            assert_eq!(ddram[..9], [0, 1, 2, 3, 4, 5, 6, 7, code], "{fallback:?}");
+        }
+    }
+
+    #[test]
+    fn emboss() {
+        let glyph = [0, 0b01000, 0, 0, 0, 0, 0b00001, 0b10000];
+        let mut canvas = Canvas::default();
+        canvas.data[..3].fill(glyph);
+        canvas.emboss(1, 1);
+        // The shadow is clipped at the right and bottom edges of the cell
+        assert_eq!(
+            canvas.data[1],
+            [0, 0b01000, 0b00100, 0, 0, 0, 0b00001, 0b10000]
+        );
+        assert_eq!([canvas.data[0], canvas.data[2]], [glyph; 2]);
+    }
+
+    #[test]
+    fn fixed_cgram_is_never_evicted() {
+        let glyphs = from_fn(|k| Bitmap::new(glyph(k as u8)));
+        let mut canvas = Canvas::with_fixed_cgram(glyphs);
+        canvas.data[0] = glyph(3);
+        canvas.data[1] = glyph(9);
+        canvas.data[2] = glyph(5);
+        canvas.data[3] = Bitmap::render('a').raw();
+        let (ddram, cgram) = canvas.render();
+        assert_eq!([ddram[0], ddram[2], ddram[3]], [3, 5, b'a']);
+        // The other glyphs fall back instead of taking a slot
+        assert!(ddram[1] >= 8);
+        assert!(cgram.iter().map(|&glyph| Bitmap::new(glyph)).eq(glyphs));
+    }
+
+    #[test]
+    fn static_glyph_cost() {
+        assert_eq!(Canvas::static_glyph_cost("Hello, world!"), 0);
+        #[cfg(feature = "latin1")]
+        assert_eq!(Canvas::static_glyph_cost("Garçon, café, ça"), 2);
+    }
+
+    #[test]
+    fn cgram_art() {
+        let heart = [0, 0b01010, 0b11111, 0b11111, 0b01110, 0b00100, 0, 0];
+        let mut canvas = Canvas::default();
+        canvas.write("a", Some(Gap::Hide));
+        canvas.data[1] = heart;
+        let (_, cgram) = canvas.render();
+        let art = Canvas::cgram_art(&cgram);
+        assert_eq!(art.len(), 1);
+        let heart = ":::::\n:#:#:\n#####\n#####\n:###:\n::#::\n:::::\n:::::\n";
+        assert_eq!(art[0].as_str(), heart);
+    }
+
+    #[test]
+    fn write_raw() {
+        let mut canvas = Canvas::default();
+        canvas.write_raw(&[0xff, 0xb0, b'-', 0x05]);
+        let (ddram, cgram) = canvas.render();
+        // NOTE:
+        // The glyph of `0xb0` is the same of `'-'`, which is the code it renders to
+        assert_eq!(ddram[..4], [0xff, b'-', b'-', 0x83]);
+        assert!(cgram.is_empty());
+    }
+
+    #[test]
+    fn blink_region() {
+        let mut canvas = Canvas::default();
+        canvas.write_line(0, "ALARM!");
+        let shown = canvas.render();
+        canvas.blink_region(0, 5, 2);
+        let mut hidden = Canvas::default();
+        hidden.write_line(0, "     !");
+        let hidden = hidden.render();
+
+        for (frame, visible) in [true, true, false, false, true].into_iter().enumerate() {
+            let expected = if visible { &shown } else { &hidden };
+            assert_eq!(canvas.render(), *expected, "frame {frame}");
+            canvas.tick();
+        }
+        assert_eq!(canvas.data, cells("ALARM!"));
+    }
+
+    #[test]
+    fn undo() {
+        let mut canvas = Canvas::default();
+        canvas.write_line(0, "abc");
+        canvas.insert_char(1, 'x');
+        canvas.delete_char(0);
+        assert_eq!(canvas.data, cells("xbc"));
+        assert!(canvas.undo());
+        assert_eq!(canvas.data, cells("axbc"));
+        assert!(canvas.undo());
+        assert_eq!(canvas.data, cells("abc"));
+        assert!(!canvas.undo());
+
+        // Only the last snapshots are kept
+        for ch in "123456".chars() {
+            canvas.insert_char(0, ch);
+        }
+        while canvas.undo() {}
+        assert_eq!(canvas.data, cells("21abc"));
+    }
+
+    #[test]
+    fn custom_glyphs_take_the_cgram_codes() {
+        use crate::lcd::{driver::Simulator, Geometry};
+
+        let heart = [0, 0b01010, 0b11111, 0b11111, 0b01110, 0b00100, 0, 0];
+        let mut canvas = Canvas::default();
+        canvas.write_line(0, "A");
+        canvas.data[1] = heart;
+        let frame = canvas.render();
+        assert_eq!(frame.0[..2], [b'A', 0]);
+        assert!(frame
+            .0
+            .iter()
+            .all(|&code| code < 8 || !ddrom::CGRAM_CODES.contains(&code)));
+
+        let geometry = Geometry {
+            columns: 16,
+            lines: 1,
+            pixel_pitch_x: 6,
+            pixel_pitch_y: 9,
+        };
+        let mut driver = Simulator::default().driver(geometry);
+        driver.present_frame(None, &frame);
+        let code = driver.pins().first.ddram[1];
+        assert_eq!(driver.pins().first.cgram_bitmap(code).raw(), heart);
+    }
+
+    #[test]
+    fn overlay() {
+        let underline = [0, 0, 0, 0, 0, 0, 0, 0b11111];
+        let mut background = Canvas::default();
+        background.write_line(0, "abc");
+        let mut sprite = Canvas::default();
+        sprite.data[1] = underline;
+        background.overlay(&sprite, CompositeOp::Or);
+
+        let mut expected = cells("abc");
+        expected[1][7] = 0b11111;
+        assert_eq!(background.data, expected);
+    }
+
+    #[test]
+    fn render_layers_at_different_rates() {
+        let mut background = Canvas::default();
+        background.write(". . . .", None);
+        let mut ticker = Canvas::default();
+        ticker.write("Hi", None);
+
+        let copy = |canvas: &Canvas| {
+            let mut copy = Canvas::default();
+            copy.restore(canvas.snapshot());
+            copy
+        };
+        let (mut below, mut above) = (copy(&background), copy(&ticker));
+        for frame in 0..4 {
+            let mut expected = copy(&below);
+            expected.overlay(&above, CompositeOp::Xor);
+            let frame = background.render_layers(frame, &ticker, 3 * frame, CompositeOp::Xor);
+            assert_eq!(frame, expected.render());
+
+            below.shift_left(None);
+            (0..3).for_each(|_| above.shift_left(None));
+        }
+    }
+
+    #[test]
+    fn measure_render_cost_matches_present_frame() {
+        use crate::lcd::{driver::Simulator, Geometry};
+
+        let heart = [0, 0b01010, 0b11111, 0b11111, 0b01110, 0b00100, 0, 0];
+        let mut canvas = Canvas {
+            lines: Lines::Two,
+            ..Canvas::default()
+        };
+        canvas.write_line(0, "abc");
+        canvas.data[9] = heart;
+        let old = canvas.render();
+
+        // A row of the first glyph changes, and a second glyph is added
+        canvas.write_line(0, "xbz");
+        canvas.data[9][0] = 0b10001;
+        canvas.data[10] = [0b10101; 8];
+        let new = canvas.render();
+
+        let geometry = Geometry {
+            columns: 8,
+            lines: 2,
+            pixel_pitch_x: 6,
+            pixel_pitch_y: 9,
+        };
+        let mut driver = Simulator::default().driver(geometry);
+        driver.present_frame(Some(&old), &new);
+        let simulator = driver.pins();
+        let cost = simulator.instructions * Command::DdRamAddress(0).exec_time_us()
+            + simulator.data_writes * WRITE_TIME_US;
+        assert_eq!((simulator.instructions, simulator.data_writes), (4, 19));
+        assert_eq!(Canvas::measure_render_cost(&old, &new), cost);
+    }
+
+    #[test]
+    fn insert_and_delete_char_outside_of_the_body() {
+        let mut canvas = Canvas {
+            lines: Lines::Two,
+            status: Some(StatusRow::Top),
+            ..Canvas::default()
+        };
+        canvas.set_status("abc");
+        canvas.insert_char(1, 'x');
+        canvas.delete_char(0);
+        canvas.insert_char(16, 'x');
+        canvas.delete_char(usize::MAX);
+        assert_eq!(canvas.data, cells("abc"));
+        assert!(!canvas.undo());
+    }
+}
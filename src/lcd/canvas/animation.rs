@@ -0,0 +1,72 @@
+use super::{Canvas, CgRam, DdRam};
+
+/// Cache of pre-rendered animation frames
+///
+/// Every frame is rendered only once, when it's pushed into the cache,
+/// so replaying the animation costs just the copy to the display.
+/// This trades some RAM (up to `N` frames) for removing the
+/// [`render`](Canvas::render) from the hot loop.
+#[derive(Default)]
+pub struct AnimationCache<const N: usize> {
+    frames: heapless::Vec<(DdRam, CgRam), N>,
+    current: usize,
+}
+
+impl<const N: usize> AnimationCache<N> {
+    /// Renders the current contents of the [`Canvas`] and appends
+    /// the result as the last frame of the animation
+    ///
+    /// If the cache is full the rendered frame is given back
+    pub fn push(&mut self, canvas: &Canvas) -> Result<(), (DdRam, CgRam)> {
+        self.frames.push(canvas.render())
+    }
+
+    /// Returns the next frame of the animation
+    ///
+    /// After the last frame the animation starts again from the first one,
+    /// [`None`] is returned only when the cache is empty
+    pub fn next_frame(&mut self) -> Option<&(DdRam, CgRam)> {
+        let frame = self.frames.get(self.current)?;
+        self.current = (self.current + 1) % self.frames.len();
+        Some(frame)
+    }
+
+    /// Number of frames in the cache
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Whether the cache contains no frames
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Restarts the animation from the first frame
+    pub fn rewind(&mut self) {
+        self.current = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replay_matches_render() {
+        let spinner = ['|', '/', '-', '<'];
+        let mut cache = AnimationCache::<4>::default();
+        let mut canvas = Canvas::default();
+        for ch in spinner {
+            canvas.write_line(0, ch.encode_utf8(&mut [0; 4]));
+            assert!(cache.push(&canvas).is_ok());
+        }
+        assert!(cache.push(&canvas).is_err());
+
+        for _ in 0..2 {
+            for ch in spinner {
+                canvas.write_line(0, ch.encode_utf8(&mut [0; 4]));
+                assert_eq!(cache.next_frame(), Some(&canvas.render()));
+            }
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use super::Canvas;
+
+/// Animation of the color of an RGB backlight
+///
+/// The colors are given as the duty cycles of the red, green and blue
+/// channels, and the periods are in frames (see [`Canvas::tick`])
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Effect {
+    /// Always the same color
+    Solid([u8; 3]),
+    /// The color fades in from black and then back out, over and over
+    Breathe { color: [u8; 3], period: u32 },
+    /// All the hues of the color wheel, one after the other
+    Rainbow { period: u32 },
+}
+
+impl Effect {
+    /// Returns the color of the backlight at the given frame
+    pub fn color_at(self, frame: u32) -> [u8; 3] {
+        match self {
+            Self::Solid(color) => color,
+            Self::Breathe { color, period } => {
+                let period = period.max(2);
+                let phase = frame % period;
+                let half = period / 2;
+                // NOTE:
+                // The level rises for the first half of the period and then falls
+                let level = if phase < half {
+                    phase * 255 / half
+                } else {
+                    (period - phase) * 255 / (period - half)
+                };
+                color.map(|channel| (u32::from(channel) * level / 255) as u8)
+            }
+            Self::Rainbow { period } => {
+                let period = period.max(1);
+                let hue = u64::from(frame % period) * 6 * 256 / u64::from(period);
+                let (sector, x) = ((hue / 256) as u8, hue as u8);
+                match sector {
+                    0 => [255, x, 0],
+                    1 => [255 - x, 255, 0],
+                    2 => [0, 255, x],
+                    3 => [0, 255 - x, 255],
+                    4 => [x, 0, 255],
+                    _ => [255, 0, 255 - x],
+                }
+            }
+        }
+    }
+}
+
+impl Canvas {
+    /// Sets the animation of the backlight
+    ///
+    /// The animation advances with the frames of the [`Canvas`], and its color
+    /// is sent to the display with [`Driver::set_backlight_color`](crate::lcd::Driver::set_backlight_color).
+    /// With a monochrome backlight the animations degrade to turning it on and off.
+    pub fn set_backlight_animation(&mut self, effect: Effect) {
+        self.backlight = Some(effect);
+    }
+
+    /// Stops the animation of the backlight
+    pub fn stop_backlight_animation(&mut self) {
+        self.backlight = None;
+    }
+
+    /// Color of the backlight at the current frame, [`None`] if there is no animation
+    pub fn backlight(&self) -> Option<[u8; 3]> {
+        self.backlight.map(|effect| effect.color_at(self.frame))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lcd::driver::Simulator;
+    use crate::lcd::Geometry;
+
+    #[test]
+    fn breathe_rises_and_falls() {
+        let geometry = Geometry {
+            columns: 8,
+            lines: 2,
+            pixel_pitch_x: 6,
+            pixel_pitch_y: 9,
+        };
+        let mut driver = Simulator::default().driver(geometry);
+        let mut canvas = Canvas::default();
+        assert_eq!(canvas.backlight(), None);
+        canvas.set_backlight_animation(Effect::Breathe {
+            color: [200, 100, 0],
+            period: 8,
+        });
+
+        let mut reds = [0; 9];
+        for (frame, red) in reds.iter_mut().enumerate() {
+            let color = canvas.backlight().unwrap();
+            *red = color[0];
+            assert_eq!(color[1], color[0] / 2);
+            // NOTE:
+            // The backlight of the simulator is monochrome, so it's off only when black
+            driver.set_backlight_color(color);
+            assert_eq!(driver.pins().backlight, frame % 8 != 0);
+            canvas.tick();
+        }
+        assert_eq!(reds, [0, 49, 99, 149, 200, 149, 99, 49, 0]);
+    }
+}
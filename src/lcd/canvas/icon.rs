@@ -0,0 +1,93 @@
+use super::ddram_address;
+use crate::lcd::{cmd::Command, Bitmap, Driver, Pins};
+
+/// Icon that blinks by switching between two glyphs kept in the **CGRAM**
+///
+/// Both the glyph shown and the one hidden are uploaded once, so each blink
+/// only rewrites the **DDRAM** byte of the icon cell.
+///
+/// **Note** that the two slots must not be used by anything else, like
+/// the glyphs assigned by [`Canvas::render`](super::Canvas::render)
+pub struct BlinkIcon {
+    /// **DDRAM** address of the cell of the icon
+    address: u8,
+    /// **CGRAM** slots of the glyphs, shown and hidden
+    slots: [u8; 2],
+    visible: bool,
+}
+
+impl BlinkIcon {
+    /// Uploads the two glyphs in the given **CGRAM** slots and shows the icon
+    /// at the given cell of the [`Canvas`](super::Canvas)
+    pub fn new<P: Pins>(
+        driver: &mut Driver<P>,
+        cell: usize,
+        [on, off]: [Bitmap; 2],
+        slots: [u8; 2],
+    ) -> Self {
+        let slots = slots.map(|slot| slot % 8);
+        for (slot, glyph) in slots.into_iter().zip([on, off]) {
+            driver.exec(Command::CgRamAddress(slot * 8));
+            glyph.raw().into_iter().for_each(|v| driver.write(v));
+        }
+        let mut icon = Self {
+            address: ddram_address(cell),
+            slots,
+            visible: true,
+        };
+        icon.show(driver, true);
+        icon
+    }
+
+    /// Whether the icon is currently shown
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Switches between the glyph shown and the one hidden
+    pub fn toggle<P: Pins>(&mut self, driver: &mut Driver<P>) {
+        self.show(driver, !self.visible);
+    }
+
+    /// Writes the code of the glyph to show in the icon cell
+    fn show<P: Pins>(&mut self, driver: &mut Driver<P>, visible: bool) {
+        self.visible = visible;
+        driver.exec(Command::DdRamAddress(self.address));
+        driver.write(self.slots[usize::from(!visible)]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lcd::driver::Simulator;
+    use crate::lcd::Geometry;
+
+    /// Geometry of the display of the tests, a WH1601L driven as 2 lines of 8 characters
+    const GEOMETRY: Geometry = Geometry {
+        columns: 8,
+        lines: 2,
+        pixel_pitch_x: 6,
+        pixel_pitch_y: 9,
+    };
+
+    #[test]
+    fn toggle_rewrites_a_single_byte() {
+        let bell = Bitmap::new([0b00100, 0b01110, 0b01110, 0b01110, 0b11111, 0, 0b00100, 0]);
+        let mut driver = Simulator::default().driver(GEOMETRY);
+        let mut icon = BlinkIcon::new(&mut driver, 9, [bell, Bitmap::default()], [2, 3]);
+        let address = usize::from(ddram_address(9));
+        assert_eq!(driver.pins().first.cgram_bitmap(2), bell);
+        assert_eq!(driver.pins().first.ddram[address], 2);
+
+        let (ddram, cgram) = (driver.pins().first.ddram, driver.pins().first.cgram);
+        let data_writes = driver.pins().data_writes;
+        icon.toggle(&mut driver);
+        assert!(!icon.is_visible());
+        assert_eq!(driver.pins().data_writes, data_writes + 1);
+        assert_eq!(driver.pins().first.cgram, cgram);
+        let changed = (0..ddram.len()).filter(|&i| driver.pins().first.ddram[i] != ddram[i]);
+        assert!(changed.eq([address]));
+        assert_eq!(driver.pins().first.ddram[address], 3);
+    }
+}
@@ -0,0 +1,79 @@
+use heapless::{HistoryBuffer, String};
+
+use super::Canvas;
+
+/// Console-like view showing the last lines of a log
+///
+/// The view keeps the last `N` lines pushed, each one truncated to `W` bytes,
+/// and shows the most recent ones on the lines of the [`Canvas`], with the
+/// newest one at the bottom, so every new line scrolls the others up.
+pub struct LogView<const W: usize, const N: usize> {
+    pub canvas: Canvas,
+    history: HistoryBuffer<String<W>, N>,
+}
+
+impl<const W: usize, const N: usize> LogView<W, N> {
+    /// Creates an empty [`LogView`] drawing on the given [`Canvas`]
+    pub fn new(canvas: Canvas) -> Self {
+        Self {
+            canvas,
+            history: HistoryBuffer::new(),
+        }
+    }
+
+    /// Appends a line to the log and updates the [`Canvas`]
+    pub fn push_line(&mut self, text: &str) {
+        let mut line = String::new();
+        for ch in text.chars() {
+            if line.push(ch).is_err() {
+                break;
+            }
+        }
+        self.history.write(line);
+        self.redraw();
+    }
+
+    /// Writes the most recent lines of the log on the [`Canvas`]
+    ///
+    /// The [`status`](Canvas::status) line, if any, is left untouched.
+    fn redraw(&mut self) {
+        let width = self.canvas.line_width();
+        let body = self.canvas.body();
+        let rows = body.start / width..body.end / width;
+        let skip = self.history.len().saturating_sub(rows.len());
+        let mut recent = self.history.oldest_ordered().skip(skip);
+        for row in rows {
+            let text = recent.next().map_or("", String::as_str);
+            self.canvas.write_line(row, text);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lcd::canvas::StatusRow;
+    use crate::lcd::cmd::Lines;
+
+    #[test]
+    fn push_line_keeps_the_status_line() {
+        let mut log = LogView::<8, 4>::new(Canvas {
+            lines: Lines::Two,
+            status: Some(StatusRow::Top),
+            ..Canvas::default()
+        });
+        log.canvas.set_status("12:00");
+        let status = log.canvas.data;
+
+        log.push_line("first");
+        log.push_line("second");
+        assert_eq!(log.canvas.data[..8], status[..8]);
+
+        let mut expected = Canvas {
+            lines: Lines::Two,
+            ..Canvas::default()
+        };
+        expected.write_line(1, "second");
+        assert_eq!(log.canvas.data[8..], expected.data[8..]);
+    }
+}
@@ -0,0 +1,93 @@
+use core::iter::repeat;
+
+use super::Canvas;
+
+/// Number of blank cells between the end of the text and its start
+/// when it's scrolling
+pub(super) const GAP: usize = 3;
+
+/// Handle to the text written by [`Canvas::auto_text`]
+///
+/// When the text didn't fit its line it scrolls one cell
+/// each time [`advance`](Self::advance) is called
+pub struct AutoText<'a> {
+    text: &'a str,
+    line: usize,
+    offset: usize,
+    scrolling: bool,
+}
+
+impl AutoText<'_> {
+    /// Whether the text is scrolling, `false` if it's static
+    pub fn is_scrolling(&self) -> bool {
+        self.scrolling
+    }
+
+    /// Scrolls the text one cell to the left
+    ///
+    /// Does nothing if the text is static
+    pub fn advance(&mut self, canvas: &mut Canvas) {
+        if !self.scrolling {
+            return;
+        }
+        self.offset = (self.offset + 1) % (self.text.chars().count() + GAP);
+        self.draw(canvas);
+    }
+
+    /// Writes the visible part of the text on its line
+    fn draw(&self, canvas: &mut Canvas) {
+        let chars = self.text.chars().chain(repeat(' ').take(GAP));
+        canvas.fill_line(self.line, chars.cycle().skip(self.offset));
+    }
+}
+
+impl Canvas {
+    /// Writes the text on the given line, scrolling it if it doesn't fit
+    ///
+    /// Text that fits the line is centered and stays still, otherwise it's
+    /// written starting from the first cell and scrolls like a marquee,
+    /// driven by the returned handle (see [`AutoText::advance`])
+    pub fn auto_text<'a>(&mut self, line: usize, text: &'a str) -> AutoText<'a> {
+        let scrolling = text.chars().count() > self.line_width();
+        let handle = AutoText {
+            text,
+            line,
+            offset: 0,
+            scrolling,
+        };
+        if scrolling {
+            handle.draw(self);
+        } else {
+            self.banner(line, text, ' ');
+        }
+        handle
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lcd::cmd::Lines;
+
+    #[test]
+    fn auto_text() {
+        let two_lines = || Canvas {
+            lines: Lines::Two,
+            ..Canvas::default()
+        };
+        let mut canvas = two_lines();
+        let mut short = canvas.auto_text(0, "Hi");
+        assert!(!short.is_scrolling());
+        let before = canvas.data;
+        short.advance(&mut canvas);
+        assert_eq!(canvas.data, before);
+
+        let mut long = canvas.auto_text(1, "Hello World");
+        assert!(long.is_scrolling());
+        long.advance(&mut canvas);
+        let mut expected = two_lines();
+        expected.banner(0, "Hi", ' ');
+        expected.write_line(1, "ello Wor");
+        assert_eq!(canvas.data, expected.data);
+    }
+}
@@ -0,0 +1,102 @@
+use super::Canvas;
+use crate::lcd::Bitmap;
+
+/// Counter whose digits roll like the ones of an odometer
+///
+/// When the value changes, each digit rolls up, one pixel for each call to
+/// [`step`](Self::step), through all the digits that separate it from the
+/// new one. The frames in between are custom glyphs, so each rolling
+/// digit takes a **CGRAM** slot while it's moving.
+#[derive(Clone, Copy, Debug)]
+pub struct Odometer<const N: usize> {
+    /// Digits currently shown, the most significant first
+    digits: [u8; N],
+    /// Digits to reach
+    targets: [u8; N],
+    /// Pixels each digit rolled towards the next one
+    offsets: [u8; N],
+}
+
+impl<const N: usize> Default for Odometer<N> {
+    fn default() -> Self {
+        Self {
+            digits: [0; N],
+            targets: [0; N],
+            offsets: [0; N],
+        }
+    }
+}
+
+impl<const N: usize> Odometer<N> {
+    /// Sets the value to roll to
+    ///
+    /// Only the `N` least significant digits are shown
+    pub fn set(&mut self, mut value: u32) {
+        for target in self.targets.iter_mut().rev() {
+            *target = (value % 10) as u8;
+            value /= 10;
+        }
+    }
+
+    /// Rolls by one pixel the digits that differ from the value to reach
+    ///
+    /// The digits always roll up, so going from `9` to `0` takes a single
+    /// digit, while going from `3` to `2` goes all the way around.
+    /// Returns `false` once all the digits reached the value.
+    pub fn step(&mut self) -> bool {
+        let mut rolling = false;
+        for i in 0..N {
+            if self.digits[i] == self.targets[i] && self.offsets[i] == 0 {
+                continue;
+            }
+            self.offsets[i] += 1;
+            if self.offsets[i] == 8 {
+                self.offsets[i] = 0;
+                self.digits[i] = (self.digits[i] + 1) % 10;
+            }
+            rolling |= self.digits[i] != self.targets[i] || self.offsets[i] != 0;
+        }
+        rolling
+    }
+
+    /// Draws the digits on the [`Canvas`], starting from the given cell
+    ///
+    /// The digits that don't fit the canvas are not drawn
+    pub fn draw(&self, canvas: &mut Canvas, cell: usize) {
+        let cells = canvas.data.iter_mut().skip(cell);
+        for (cell, (&digit, &offset)) in cells.zip(self.digits.iter().zip(&self.offsets)) {
+            let glyph = |digit: u8| Bitmap::render(char::from(b'0' + digit));
+            *cell = glyph(digit).roll(glyph((digit + 1) % 10), offset).raw();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roll_from_3_to_4() {
+        let mut odometer = Odometer::<1>::default();
+        odometer.set(3);
+        while odometer.step() {}
+        let mut canvas = Canvas::default();
+        odometer.draw(&mut canvas, 2);
+        let (three, four) = (Bitmap::render('3'), Bitmap::render('4'));
+        assert_eq!(canvas.data[2], three.raw());
+
+        odometer.set(4);
+        for px in 1..8 {
+            assert!(odometer.step());
+            odometer.draw(&mut canvas, 2);
+            assert_eq!(canvas.data[2], three.roll(four, px).raw());
+            assert_eq!(
+                canvas.data[2][..8 - usize::from(px)],
+                three.raw()[usize::from(px)..]
+            );
+        }
+        assert!(!odometer.step());
+        odometer.draw(&mut canvas, 2);
+        assert_eq!(canvas.data[2], four.raw());
+    }
+}
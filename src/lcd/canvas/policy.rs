@@ -0,0 +1,71 @@
+use super::CgRam;
+use crate::lcd::{ddrom, Bitmap};
+
+/// Strategy used to assign the **CGRAM** slots to the glyphs that
+/// are not present in the **DDROM**
+pub trait CgramPolicy {
+    /// Returns the code to show in place of the given glyph
+    ///
+    /// This is called only for the glyphs that are found neither in
+    /// the **DDROM** nor in `cgram`: the policy can either push the glyph
+    /// in `cgram` and return its index, return the code of another glyph,
+    /// or return [`None`] to leave the choice to the [`Canvas`](super::Canvas).
+    /// Once a glyph is pushed, the following cells with the same glyph reuse
+    /// its slot without calling the policy again.
+    fn allocate(&mut self, glyph: Bitmap, cgram: &mut CgRam) -> Option<u8>;
+}
+
+/// Assigns the **CGRAM** slots in order of appearance, until they run out
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Fifo;
+
+impl CgramPolicy for Fifo {
+    fn allocate(&mut self, glyph: Bitmap, cgram: &mut CgRam) -> Option<u8> {
+        cgram.push(glyph.raw()).map(|_| cgram.len() as u8 - 1).ok()
+    }
+}
+
+/// Approximates with the **DDROM** the glyphs that are close enough
+/// to one of its bitmaps, and assigns the **CGRAM** slots only to the others
+///
+/// This keeps the slots free for the glyphs that really need them
+/// (e.g. the partially filled ones of a gauge)
+#[derive(Clone, Copy, Debug)]
+pub struct Nearest {
+    /// Maximum [`distance`](Bitmap::distance) of the approximation
+    pub threshold: u32,
+}
+
+impl CgramPolicy for Nearest {
+    fn allocate(&mut self, glyph: Bitmap, cgram: &mut CgRam) -> Option<u8> {
+        let (code, distance) = ddrom::approx(glyph);
+        if distance <= self.threshold {
+            return Some(code);
+        }
+        Fifo.allocate(glyph, cgram)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lcd::canvas::Canvas;
+
+    #[test]
+    fn policies_keep_different_glyphs() {
+        let heart = [0, 0b01010, 0b11111, 0b11111, 0b01110, 0b00100, 0, 0];
+        let mut almost_a = Bitmap::render('A').raw();
+        almost_a[7] = 0b00001;
+        let mut canvas = Canvas::default();
+        canvas.data[0] = almost_a;
+        canvas.data[1] = heart;
+
+        let (ddram, cgram) = canvas.render_with(&mut Fifo);
+        assert_eq!(ddram[..2], [0, 1]);
+        assert_eq!(cgram[..], [almost_a, heart]);
+
+        let (ddram, cgram) = canvas.render_with(&mut Nearest { threshold: 1 });
+        assert_eq!(ddram[..2], [b'A', 0]);
+        assert_eq!(cgram[..], [heart]);
+    }
+}
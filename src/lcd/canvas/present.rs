@@ -0,0 +1,185 @@
+use super::Canvas;
+use crate::lcd::{ddrom, Driver, Pins};
+
+impl Canvas {
+    /// Returns a hash of the frame that [`render`](Self::render) would produce
+    ///
+    /// Two canvases with the same hash show the same thing (barring
+    /// collisions), so the hash can be used to skip redundant updates
+    pub fn frame_hash(&self) -> u64 {
+        // NOTE:
+        // This is the 64 bit FNV-1a hash
+        const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+        const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+        // NOTE:
+        // The data is the same that `render_data` turns into glyphs,
+        // so that each setting that changes the frame changes the hash too
+        let data = self.blinked(self.inverted(self.data));
+        let bytes = data.iter().flatten().map(|&v| v & 0b11111);
+        bytes
+            .chain([u8::from(self.approx), self.fallback.code()])
+            .fold(OFFSET, |hash, byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+            })
+    }
+
+    /// Updates the display with the contents of the [`Canvas`], if they changed
+    ///
+    /// When the [`frame_hash`](Self::frame_hash) is the same as `prev_hash`
+    /// nothing is sent to the display. Otherwise the canvas is rendered and
+    /// only the **CGRAM** slots and the **DDRAM** cells that differ from the
+    /// last frame presented are written (see [`Driver::present_frame`]),
+    /// then `prev_hash` is updated.
+    /// Returns whether the display was updated.
+    ///
+    /// **Note** that this expects the cursor to be moving to the right
+    /// (see [`EntryMode`](crate::lcd::cmd::Command::EntryMode))
+    pub fn present_if_changed<P: Pins>(
+        &mut self,
+        driver: &mut Driver<P>,
+        prev_hash: &mut u64,
+    ) -> bool {
+        let hash = self.frame_hash();
+        if hash == *prev_hash {
+            return false;
+        }
+
+        let frame = self.render();
+        driver.present_frame(self.presented.as_ref(), &frame);
+        self.presented = Some(frame);
+        *prev_hash = hash;
+        true
+    }
+
+    /// Updates the display with the contents of the [`Canvas`] within a time budget
+    ///
+    /// When writing the whole frame would take more than `budget_us` (as
+    /// estimated by [`measure_render_cost`](Self::measure_render_cost)) only
+    /// part of the **DDRAM** is written: first the cells of the
+    /// [`priority`](Self::priority) region, whatever their cost, then the
+    /// others, in order, as long as the estimate fits the budget. The cells
+    /// left behind are written by the following calls, so that the parts
+    /// that matter stay responsive under heavy updates.
+    ///
+    /// The **CGRAM** is always written whole, along with the cells whose glyph
+    /// would change because of it, and the first frame presented is always
+    /// written whole. Returns whether the whole frame was written.
+    ///
+    /// **Note** that this expects the cursor to be moving to the right
+    /// (see [`EntryMode`](crate::lcd::cmd::Command::EntryMode))
+    pub fn present_within<P: Pins>(&mut self, driver: &mut Driver<P>, budget_us: u32) -> bool {
+        let frame = self.render();
+        let Some(old) = self.presented.take() else {
+            driver.present_frame(None, &frame);
+            self.presented = Some(frame);
+            return true;
+        };
+
+        let priority = self.priority.clone().unwrap_or_default();
+        let mut partial = (old.0, frame.1.clone());
+        for (i, cell) in partial.0.iter_mut().enumerate() {
+            // NOTE:
+            // The codes of the CGRAM are mirrored, so 8 shows the same glyph of 0
+            let slot = usize::from(*cell % 8);
+            let custom = ddrom::CGRAM_CODES.contains(cell);
+            if priority.contains(&i) || custom && old.1.get(slot) != frame.1.get(slot) {
+                *cell = frame.0[i];
+            }
+        }
+        for i in 0..partial.0.len() {
+            let mut next = partial.clone();
+            next.0[i] = frame.0[i];
+            if Self::measure_render_cost(&old, &next) > budget_us {
+                break;
+            }
+            partial = next;
+        }
+
+        driver.present_frame(Some(&old), &partial);
+        let done = partial == frame;
+        self.presented = Some(partial);
+        done
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lcd::canvas::{ddram_address, DdRam};
+    use crate::lcd::cmd::{Command, Lines, WRITE_TIME_US};
+    use crate::lcd::driver::Simulator;
+    use crate::lcd::Geometry;
+
+    /// Geometry of the display of the tests, a WH1601L driven as 2 lines of 8 characters
+    const GEOMETRY: Geometry = Geometry {
+        columns: 8,
+        lines: 2,
+        pixel_pitch_x: 6,
+        pixel_pitch_y: 9,
+    };
+
+    /// Codes shown in the cells of the [`Canvas`] by the simulated display
+    fn shown(driver: &mut Driver<'_, Simulator>) -> DdRam {
+        let ddram = driver.pins().first.ddram;
+        core::array::from_fn(|i| ddram[usize::from(ddram_address(i))])
+    }
+
+    #[test]
+    fn present_within_sends_the_priority_first() {
+        let mut canvas = Canvas {
+            lines: Lines::Two,
+            priority: Some(12..16),
+            ..Canvas::default()
+        };
+        let mut driver = Simulator::default().driver(GEOMETRY);
+        assert!(canvas.present_within(&mut driver, 0));
+        let blank = shown(&mut driver);
+
+        // The budget only fits the priority region
+        canvas.write_line(0, "abcdefgh");
+        canvas.write_line(1, "ijklmnop");
+        let (frame, _) = canvas.render();
+        let budget = Command::DdRamAddress(0).exec_time_us() + 4 * WRITE_TIME_US;
+        assert!(!canvas.present_within(&mut driver, budget));
+        let ddram = shown(&mut driver);
+        assert_eq!(ddram[12..], frame[12..]);
+        assert_eq!(ddram[..12], blank[..12]);
+
+        // The rest is written by the following call
+        assert!(canvas.present_within(&mut driver, u32::MAX));
+        assert_eq!(shown(&mut driver), frame);
+    }
+
+    #[test]
+    fn present_if_changed_skips_unchanged_frames() {
+        let mut canvas = Canvas {
+            lines: Lines::Two,
+            ..Canvas::default()
+        };
+        let mut driver = Simulator::default().driver(GEOMETRY);
+        let mut hash = 0;
+        canvas.write_line(0, "abc");
+        assert!(canvas.present_if_changed(&mut driver, &mut hash));
+
+        let (instructions, data_writes) = (driver.pins().instructions, driver.pins().data_writes);
+        assert!(!canvas.present_if_changed(&mut driver, &mut hash));
+        assert_eq!(driver.pins().instructions, instructions);
+        assert_eq!(driver.pins().data_writes, data_writes);
+
+        canvas.write_line(1, "d");
+        assert!(canvas.present_if_changed(&mut driver, &mut hash));
+        assert!(driver.pins().data_writes > data_writes);
+    }
+
+    #[test]
+    fn frame_hash_follows_inversion() {
+        let mut canvas = Canvas::default();
+        canvas.write("Hi", None);
+        let hash = canvas.frame_hash();
+        canvas.set_cell_invert(1, true);
+        assert_ne!(canvas.frame_hash(), hash);
+        canvas.set_cell_invert(1, false);
+        assert_eq!(canvas.frame_hash(), hash);
+    }
+}
@@ -0,0 +1,102 @@
+use super::Canvas;
+use crate::lcd::Bitmap;
+
+/// A [`Bitmap`] placed anywhere on the pixels of a [`Canvas`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Sprite {
+    pub bitmap: Bitmap,
+    /// Column of the pixels of the [`Canvas`] of the left edge of the sprite
+    pub x_px: i16,
+    /// Row of the pixels of the [`Canvas`] of the top edge of the sprite
+    pub y_px: i16,
+    /// Depth of the sprite, the ones with higher values are drawn on top
+    pub z: i8,
+}
+
+/// Set of at most `N` [`Sprite`]s drawn together on a [`Canvas`]
+///
+/// See [`Canvas::draw_sprites`]
+#[derive(Clone, Debug, Default)]
+pub struct SpriteLayer<const N: usize> {
+    pub sprites: heapless::Vec<Sprite, N>,
+}
+
+impl Canvas {
+    /// Draws the [`Sprite`]s of the layer on the [`Canvas`]
+    ///
+    /// The pixels of the canvas are seen as a single grid, with the cells
+    /// of each line side by side and the lines one below the other,
+    /// ignoring the gap between them. Each sprite covers all the 5 by 8
+    /// pixels under it, and they are drawn from the lowest `z` to the
+    /// highest, so where they overlap the highest one is shown.
+    /// The parts of the sprites that fall outside of the canvas are clipped.
+    pub fn draw_sprites<const N: usize>(&mut self, layer: &SpriteLayer<N>) {
+        let width = 5 * self.line_width() as i16;
+        let height = 8 * self.line_count() as i16;
+
+        let mut order: heapless::Vec<(i8, usize), N> = heapless::Vec::new();
+        for (i, sprite) in layer.sprites.iter().enumerate() {
+            let visible = sprite.x_px > -5 && sprite.x_px < width;
+            if visible && sprite.y_px > -8 && sprite.y_px < height {
+                // NOTE:
+                // There are at most `N` sprites, so this can't fail
+                let _ = order.push((sprite.z, i));
+            }
+        }
+        order.sort_unstable();
+
+        for (_, i) in order {
+            let sprite = layer.sprites[i];
+            for (dy, row) in sprite.bitmap.raw().into_iter().enumerate() {
+                for dx in 0..5 {
+                    let x = sprite.x_px + dx;
+                    let y = sprite.y_px + dy as i16;
+                    if (0..width).contains(&x) && (0..height).contains(&y) {
+                        self.set_pixel(x as usize, y as usize, row >> (4 - dx) & 1 != 0);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Sets the pixel at the given coordinates of the grid of [`draw_sprites`](Self::draw_sprites)
+    fn set_pixel(&mut self, x: usize, y: usize, on: bool) {
+        let cell = y / 8 * self.line_width() + x / 5;
+        let bit = 1 << (4 - x % 5);
+        let row = &mut self.data[cell][y % 8];
+        if on {
+            *row |= bit;
+        } else {
+            *row &= !bit;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn higher_sprite_wins_the_shared_pixels() {
+        let block = Sprite {
+            bitmap: Bitmap::new([0b11111; 8]),
+            ..Sprite::default()
+        };
+        let blank = Sprite {
+            x_px: 3,
+            z: 1,
+            ..Sprite::default()
+        };
+        let mut layer = SpriteLayer::<2>::default();
+        layer.sprites.extend([blank, block]);
+
+        let mut canvas = Canvas::default();
+        canvas.draw_sprites(&layer);
+        assert_eq!(canvas.data[0], [0b11100; 8]);
+
+        layer.sprites[0].z = -1;
+        canvas.draw_sprites(&layer);
+        assert_eq!(canvas.data[0], [0b11111; 8]);
+        assert_eq!(canvas.data[1], [0; 8]);
+    }
+}
@@ -0,0 +1,152 @@
+use core::iter::repeat;
+use core::mem::replace;
+
+use super::marquee::GAP;
+use super::Canvas;
+
+/// Line of a [`Canvas`] made of a label, a value and a message
+///
+/// The label takes the first cells of the line and is followed by the value,
+/// which is right aligned in a field of fixed width, while the message takes
+/// the rest of the line and scrolls like a marquee when it doesn't fit.
+/// Each field is drawn again only after it changes, so for example updating
+/// the value leaves the cells of the label and of the message untouched.
+pub struct StatusLine<'a> {
+    line: usize,
+    label: &'a str,
+    value: heapless::String<16>,
+    message: &'a str,
+    /// Cell of the line where the value starts
+    value_at: usize,
+    /// Cell of the line where the message starts
+    message_at: usize,
+    /// Cells of the line
+    width: usize,
+    /// Characters the message is scrolled by
+    offset: usize,
+    /// Fields changed since they were last drawn, as label, value and message
+    dirty: [bool; 3],
+}
+
+impl<'a> StatusLine<'a> {
+    /// Creates the fields on the given line of the [`Canvas`]
+    ///
+    /// The value takes `value_width` cells after the label, both are cut
+    /// when they don't fit the line. All the fields are drawn by the
+    /// first call to [`draw`](Self::draw).
+    pub fn new(canvas: &Canvas, line: usize, label: &'a str, value_width: usize) -> Self {
+        let width = canvas.line_width();
+        let value_at = label.chars().count().min(width);
+        Self {
+            line,
+            label,
+            value: heapless::String::new(),
+            message: "",
+            value_at,
+            message_at: (value_at + value_width).min(width),
+            width,
+            offset: 0,
+            dirty: [true; 3],
+        }
+    }
+
+    /// Sets the value shown after the label
+    ///
+    /// Only the first characters that fit in the field are kept
+    pub fn set_value(&mut self, value: &str) {
+        let mut new = heapless::String::<16>::new();
+        for ch in value.chars().take(self.message_at - self.value_at) {
+            // NOTE:
+            // There are at most 16 cells, so this can't fail
+            let _ = new.push(ch);
+        }
+        if new != self.value {
+            self.value = new;
+            self.dirty[1] = true;
+        }
+    }
+
+    /// Sets the message shown in the rest of the line, starting from its beginning
+    pub fn set_message(&mut self, message: &'a str) {
+        if message != self.message {
+            self.message = message;
+            self.offset = 0;
+            self.dirty[2] = true;
+        }
+    }
+
+    /// Scrolls the message one character to the left
+    ///
+    /// Does nothing if the message fits its field
+    pub fn advance(&mut self) {
+        let len = self.message.chars().count();
+        if len > self.width - self.message_at {
+            self.offset = (self.offset + 1) % (len + GAP);
+            self.dirty[2] = true;
+        }
+    }
+
+    /// Draws the fields that changed since the last time on the [`Canvas`]
+    ///
+    /// Returns the number of cells written
+    pub fn draw(&mut self, canvas: &mut Canvas) -> usize {
+        let mut written = 0;
+        if replace(&mut self.dirty[0], false) {
+            canvas.fill_cells(self.line, 0..self.value_at, self.label.chars());
+            written += self.value_at;
+        }
+        if replace(&mut self.dirty[1], false) {
+            let field = self.value_at..self.message_at;
+            let pad = field.len() - self.value.chars().count();
+            let chars = repeat(' ').take(pad).chain(self.value.chars());
+            written += field.len();
+            canvas.fill_cells(self.line, field, chars);
+        }
+        if replace(&mut self.dirty[2], false) {
+            let field = self.message_at..self.width;
+            let chars = self.message.chars().chain(repeat(' ').take(GAP));
+            // NOTE:
+            // A message that fits its field is shown once, not repeated
+            let shown = self.message.chars().count().min(field.len());
+            written += field.len();
+            let chars = chars.cycle().skip(self.offset).take(shown);
+            canvas.fill_cells(self.line, field, chars);
+        }
+        written
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lcd::Bitmap;
+
+    #[test]
+    fn value_updates_leave_the_other_fields() {
+        let mut canvas = Canvas::default();
+        let mut status = StatusLine::new(&canvas, 0, "T:", 4);
+        status.set_value("21");
+        status.set_message("hello world");
+        assert_eq!(status.draw(&mut canvas), 16);
+        let before = canvas.data;
+
+        status.set_value("22");
+        assert_eq!(status.draw(&mut canvas), 4);
+        for (i, (cell, ch)) in canvas
+            .data
+            .iter()
+            .zip("T:  22hello worl".chars())
+            .enumerate()
+        {
+            assert_eq!(*cell, Bitmap::render(ch).raw(), "cell {i}");
+        }
+        assert_eq!(canvas.data[..5], before[..5]);
+        assert_eq!(canvas.data[6..], before[6..]);
+
+        // Nothing changed, so nothing is drawn
+        status.set_value("22");
+        assert_eq!(status.draw(&mut canvas), 0);
+        status.advance();
+        assert_eq!(status.draw(&mut canvas), 10);
+    }
+}
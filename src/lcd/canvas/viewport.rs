@@ -0,0 +1,128 @@
+use super::Canvas;
+use crate::lcd::Bitmap;
+
+/// Grid of `W` by `H` cells larger than the [`Canvas`], of which only
+/// the part under the viewport is shown
+///
+/// The viewport has the size of the canvas, without its
+/// [`status`](Canvas::status) line, and it's moved over the grid with
+/// [`pan`](Self::pan), which allows to scroll in both directions contents
+/// like maps and long menus.
+#[derive(Clone, Copy, Debug)]
+pub struct Viewport<const W: usize, const H: usize> {
+    cells: [[Bitmap; W]; H],
+    /// Column of the grid at the left edge of the viewport
+    x: usize,
+    /// Line of the grid at the top edge of the viewport
+    y: usize,
+    /// Cells of each line of the viewport
+    width: usize,
+    /// Lines of the viewport
+    height: usize,
+}
+
+impl<const W: usize, const H: usize> Viewport<W, H> {
+    /// Creates a blank grid with the viewport at its top-left corner
+    pub fn new(canvas: &Canvas) -> Self {
+        let width = canvas.line_width();
+        Self {
+            cells: [[Bitmap::default(); W]; H],
+            x: 0,
+            y: 0,
+            width,
+            height: canvas.body().len() / width,
+        }
+    }
+
+    /// Writes the text on the grid, starting from the given cell
+    ///
+    /// The characters that don't fit the line of the grid are dropped
+    pub fn write(&mut self, x: usize, y: usize, text: &str) {
+        let Some(line) = self.cells.get_mut(y) else {
+            return;
+        };
+        for (cell, ch) in line.iter_mut().skip(x).zip(text.chars()) {
+            *cell = Bitmap::render(ch);
+        }
+    }
+
+    /// Sets the glyph of the given cell of the grid
+    pub fn set(&mut self, x: usize, y: usize, glyph: Bitmap) {
+        if let Some(cell) = self.cells.get_mut(y).and_then(|line| line.get_mut(x)) {
+            *cell = glyph;
+        }
+    }
+
+    /// Moves the viewport by the given number of cells to the right and down
+    ///
+    /// The viewport stops at the edges of the grid, and it stays
+    /// at the top-left corner when the grid is smaller than it
+    pub fn pan(&mut self, dx: i32, dy: i32) {
+        let clamp = |pos: usize, delta: i32, max: usize| {
+            let pos = pos as i64 + i64::from(delta);
+            pos.clamp(0, max as i64) as usize
+        };
+        self.x = clamp(self.x, dx, W.saturating_sub(self.width));
+        self.y = clamp(self.y, dy, H.saturating_sub(self.height));
+    }
+
+    /// Cell of the grid at the top-left corner of the viewport, as `(x, y)`
+    pub fn position(&self) -> (usize, usize) {
+        (self.x, self.y)
+    }
+
+    /// Draws the part of the grid under the viewport on the [`Canvas`]
+    ///
+    /// The cells of the viewport outside of the grid are left blank
+    pub fn draw(&self, canvas: &mut Canvas) {
+        let start = canvas.body().start;
+        for row in 0..self.height {
+            for column in 0..self.width {
+                let cell = self
+                    .cells
+                    .get(self.y + row)
+                    .and_then(|l| l.get(self.x + column));
+                let x = start + row * self.width + column;
+                canvas.data[x] = cell.copied().unwrap_or_default().raw();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lcd::cmd::Lines;
+
+    /// Glyphs of the given text, one for each cell of a canvas of 2 lines
+    fn cells(text: &str) -> [[u8; 8]; 16] {
+        let mut chars = text.chars();
+        core::array::from_fn(|_| chars.next().map_or([0; 8], |ch| Bitmap::render(ch).raw()))
+    }
+
+    #[test]
+    fn pan_over_the_grid() {
+        let mut canvas = Canvas {
+            lines: Lines::Two,
+            ..Canvas::default()
+        };
+        let mut viewport = Viewport::<10, 3>::new(&canvas);
+        viewport.write(0, 0, "abcdefghij");
+        viewport.write(0, 1, "klmnopqrst");
+        viewport.write(0, 2, "uvwxyz0123");
+        viewport.draw(&mut canvas);
+        assert_eq!(canvas.data, cells("abcdefghklmnopqr"));
+
+        viewport.pan(1, 1);
+        viewport.draw(&mut canvas);
+        assert_eq!(canvas.data, cells("lmnopqrsvwxyz012"));
+
+        // The viewport stops at the edges of the grid
+        viewport.pan(5, 5);
+        assert_eq!(viewport.position(), (2, 1));
+        viewport.draw(&mut canvas);
+        assert_eq!(canvas.data, cells("mnopqrstwxyz0123"));
+        viewport.pan(-9, -9);
+        assert_eq!(viewport.position(), (0, 0));
+    }
+}
@@ -1,5 +1,14 @@
+use core::ops::Range;
+
 use super::Bitmap;
 
+/// Codes that address the **CGRAM** instead of the **DDROM**
+///
+/// The glyphs of the **CGRAM** are shown by the codes from `0x00` to `0x07`,
+/// and mirrored by the ones from `0x08` to `0x0f`, so none of these can
+/// ever refer to a glyph of the **DDROM**
+pub const CGRAM_CODES: Range<u8> = 0x00..0x10;
+
 /// Search for a [`Bitmap`] inside the **DDROM**
 ///
 /// # Return
@@ -8,14 +17,67 @@ use super::Bitmap;
 /// is returned containing the address in the **DDROM**
 /// of that [`Bitmap`].
 /// Otherwise [`None`] is returned.
+///
+/// The returned address is never one of the [`CGRAM_CODES`]
 pub fn search(char: Bitmap) -> Option<u8> {
-    MAP.get(&char.raw()).copied()
+    MAP.get(&char.raw())
+        .copied()
+        .filter(|code| !CGRAM_CODES.contains(code))
+}
+
+/// Returns the address in the **DDROM** of the given character
+///
+/// Only the characters that share their code with ASCII are supported,
+/// along with the ones that took the place of `'\\'` and `'~'`
+pub fn encode(ch: char) -> Option<u8> {
+    match ch {
+        '¥' => Some(0x5c),
+        '→' => Some(0x7e),
+        '←' => Some(0x7f),
+        '\\' | '~' => None,
+        ' '..='}' => Some(ch as u8),
+        _ => None,
+    }
+}
+
+/// Returns the character at the given address of the **DDROM**
+///
+/// This is the inverse of [`encode`], so it only supports the same characters
+pub fn decode(code: u8) -> Option<char> {
+    match code {
+        0x5c => Some('¥'),
+        0x7e => Some('→'),
+        0x7f => Some('←'),
+        b' '..=b'}' => Some(code as char),
+        _ => None,
+    }
+}
+
+/// Addresses of the **DDROM** whose [`Bitmap`] is the same of another
+/// address, paired with the one [`search`] returns for it
+const ALIASES: [(u8, u8); 2] = [(b' ', 0x83), (0xb0, b'-')];
+
+/// Returns the [`Bitmap`] at the given address of the **DDROM**
+///
+/// This is the inverse of [`search`], except for the addresses whose
+/// [`Bitmap`] is the same of another one, where [`search`] returns the other.
+/// [`None`] is returned for the [`CGRAM_CODES`] and the unused addresses.
+pub fn glyph(code: u8) -> Option<Bitmap> {
+    let code = ALIASES
+        .iter()
+        .find(|&&(alias, _)| alias == code)
+        .map_or(code, |&(_, code)| code);
+    all().find(|&(_, c)| c == code).map(|(glyph, _)| glyph)
 }
 
 /// Returns an [`Iterator`] over all of the [`Bitmap`]s present
 /// in the **DDROM** paired with their respective addresses
+///
+/// None of the addresses is one of the [`CGRAM_CODES`]
 pub fn all() -> impl Iterator<Item = (Bitmap, u8)> {
-    MAP.entries().map(|(&a, &b)| (Bitmap::new(a), b))
+    MAP.entries()
+        .filter(|(_, code)| !CGRAM_CODES.contains(code))
+        .map(|(&a, &b)| (Bitmap::new(a), b))
 }
 
 /// Finds the [`Bitmap`] in the **DDROM** that most closely
@@ -24,13 +86,12 @@ pub fn all() -> impl Iterator<Item = (Bitmap, u8)> {
 /// # Return
 ///
 /// This function returns a two-element tuple containing:
-/// 0) the distance from the given [`Bitmap`]
-/// 1) the address in the **DDROM**
+/// 0) the address in the **DDROM**
+/// 1) the distance from the given [`Bitmap`]
+///
+/// This is the same as [`Bitmap::nearest_in_ddrom`]
 pub fn approx(char: Bitmap) -> (u8, u32) {
-    all()
-        .map(|(key, val)| (val, char.distance(key)))
-        .min_by_key(|(_, d)| *d)
-        .unwrap()
+    char.nearest_in_ddrom()
 }
 
 /// Map every bitmap present in the DDROM to its respective address
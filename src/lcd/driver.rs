@@ -9,33 +9,72 @@
 //! The following example shows how to initialize the display and
 //! write `"Hello"` on it:
 //! ```
-//! # use esp_test::lcd::{Driver, Pins, Result, cmd::Lines, cmd::Font};
+//! # use esp_test::lcd::{canvas::Canvas, cmd::*, Driver, Geometry};
 //! # fn main() {
-//! let lcd = Driver::setup(Pins { ..todo!() });
+//! # let (pins, clocks) = todo!();
+//! let geometry = Geometry {
+//!     columns: 8,
+//!     lines: 2,
+//!     pixel_pitch_x: 6,
+//!     pixel_pitch_y: 9,
+//! };
+//! let mut lcd = Driver::setup(pins, geometry, &clocks);
 //!
 //! // Inizialization: specify number of lines and font size
-//! lcd.function_set(Lines::Two, Font::Size5x2);
+//! lcd.exec(Command::FunctionSet {
+//!     lines: Lines::Two,
+//!     font: Font::Size5x8,
+//! });
+//!
+//! // Clear the display contents
+//! lcd.exec(Command::Clear());
 //!
 //! // Enable the display and the cursor (disable blinking)
-//! lcd.on_off(true, true, false);
+//! lcd.exec(Command::Onoff {
+//!     display: true,
+//!     cursor: true,
+//!     blink: false,
+//! });
 //!
-//! // Clear the display contents
-//! lcd.clear();
+//! // Move the cursor to the right after each write
+//! lcd.exec(Command::EntryMode {
+//!     cursor: Direction::Right,
+//!     display: false,
+//! });
 //!
 //! // Write "Hello"
-//! lcd.write(b'H');
-//! lcd.write(b'e');
-//! lcd.write(b'l');
-//! lcd.write(b'l');
-//! lcd.write(b'o');
+//! let mut canvas = Canvas::default();
+//! canvas.write("Hello", None);
+//! lcd.present_frame(None, &canvas.render());
 //! # }
 //! ```
 
+use core::array::from_fn;
+use core::mem::replace;
+
+use hal::xtensa_lx::timer::get_cycle_count;
 use hal::{clock::Clocks, delay::Delay};
 
+use super::canvas::{ddram_address, CgRam, DdRam};
+use super::{ddrom, Bitmap};
+
 // pub mod bus;
 pub mod cmd;
 
+mod geometry;
+pub use geometry::Geometry;
+
+mod multi;
+pub use multi::MultiDriver;
+
+mod perf;
+pub use perf::{OpStats, PerfStats};
+
+#[cfg(test)]
+mod sim;
+#[cfg(test)]
+pub(crate) use sim::Simulator;
+
 pub trait Pins {
     /// Register select pin
     ///
@@ -57,49 +96,846 @@ pub trait Pins {
     ///
     /// This pin starts the read or write operation
     fn set_en(&mut self, value: bool);
+    /// Enable pin of the second controller
+    ///
+    /// Only the displays driven by two controllers (see [`Geometry::controllers`])
+    /// have this pin, so the default implementation does nothing
+    fn set_en2(&mut self, _value: bool) {}
 
     fn write(&mut self, value: u8);
     fn read(&mut self) -> u8;
+    /// Whether the data pins can be read
+    ///
+    /// Some wirings can only write to the display (e.g. through a shift
+    /// register), so [`read`](Self::read) would return garbage.
+    /// The default implementation returns `true`
+    fn can_read(&self) -> bool {
+        true
+    }
+    /// Reads only the `D7` line, which carries the busy flag
+    ///
+    /// This is for the wirings where only `D7` can be read, or where reading
+    /// it alone is cheaper than switching the whole bus to input.
+    /// The default implementation returns [`None`], meaning it's not supported
+    fn read_d7(&mut self) -> Option<bool> {
+        None
+    }
+
+    /// Backlight control
+    ///
+    /// Turns the backlight on or off, the default implementation does nothing
+    /// for the wirings where the backlight can't be controlled
+    fn set_backlight(&mut self, _on: bool) {}
+
+    /// Backlight color
+    ///
+    /// Sets the duty cycle of the red, green and blue channels of an RGB
+    /// backlight, from `0` (off) to `255` (fully on). The default
+    /// implementation is for the monochrome backlights, which are
+    /// turned on unless the color is black (see [`set_backlight`](Self::set_backlight))
+    fn set_backlight_color(&mut self, rgb: [u8; 3]) {
+        self.set_backlight(rgb != [0; 3]);
+    }
+
+    /// Reset pin
+    ///
+    /// Asserts or releases the reset of the controllers that expose it:
+    /// - `true` ⇒ **LOW** (reset)
+    /// - `false` ⇒ **HIGH**
+    ///
+    /// The default implementation does nothing, for the controllers
+    /// and the wirings without a reset pin
+    fn set_reset(&mut self, _active: bool) {}
+}
+
+/// Time waited by the [`Driver`] after each operation
+///
+/// The default values are the worst-cases indicated by the datasheet,
+/// [`Driver::calibrate`] can be used to replace them with the ones
+/// measured on the connected display, or they can be learned while
+/// polling the busy flag (see [`Driver::set_busy_polling`]).
+#[derive(Clone, Copy, Debug)]
+pub struct Timing {
+    /// Execution time in µs of [`Clear`](cmd::Command::Clear)
+    /// and [`ReturnHome`](cmd::Command::ReturnHome)
+    pub long_us: u32,
+    /// Execution time in µs of all the other commands
+    pub short_us: u32,
+    /// Execution time in µs of a data read or write
+    pub data_us: u32,
+    /// Time in ns the enable pin is kept high when writing,
+    /// the value is latched on its falling edge
+    pub write_pulse_ns: u32,
+    /// Time in ns the value is kept on the bus after the falling edge
+    /// of the enable pin when writing
+    pub write_hold_ns: u32,
+    /// Time in ns the enable pin is kept high when reading,
+    /// before sampling the value on the bus
+    ///
+    /// The value must be on the bus by then (t_DDR), and the enable pin
+    /// must be kept high as long as when writing (PW_EH)
+    pub read_pulse_ns: u32,
+}
+
+impl Default for Timing {
+    fn default() -> Self {
+        Self {
+            long_us: cmd::Command::Clear().exec_time_us(),
+            short_us: cmd::Command::DdRamAddress(0).exec_time_us(),
+            data_us: cmd::WRITE_TIME_US,
+            write_pulse_ns: cmd::ENABLE_PULSE_NS,
+            write_hold_ns: cmd::DATA_HOLD_NS,
+            read_pulse_ns: cmd::READ_DELAY_NS.max(cmd::ENABLE_PULSE_NS),
+        }
+    }
+}
+
+impl Timing {
+    /// Raises the enable pulses to the minimum width allowed by the datasheet (PW_EH)
+    fn within_spec(self) -> Self {
+        Self {
+            write_pulse_ns: self.write_pulse_ns.max(cmd::ENABLE_PULSE_NS),
+            read_pulse_ns: self.read_pulse_ns.max(cmd::ENABLE_PULSE_NS),
+            ..self
+        }
+    }
+}
+
+/// Longest busy durations observed for each kind of operation of the [`Driver`]
+///
+/// The kinds are the same of the [`Timing`], a value of [`None`]
+/// means that no operation of that kind was observed yet
+#[derive(Clone, Copy, Debug, Default)]
+struct Learned {
+    long_us: Option<u32>,
+    short_us: Option<u32>,
+    data_us: Option<u32>,
 }
 
+/// Controllers targeted by the operations of the [`Driver`]
+///
+/// This only matters for the displays driven by two controllers,
+/// see [`Geometry::controllers`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Controller {
+    /// The controller of the first two lines
+    #[default]
+    First,
+    /// The controller of the third and fourth lines
+    Second,
+    /// Both controllers, used for commands and **CGRAM** writes
+    ///
+    /// Reads are always done from the [`First`](Self::First) one
+    Both,
+}
+
+/// Last values sent of the commands that control the state of the display
+#[derive(Clone, Copy, Debug, Default)]
+struct State {
+    entry_mode: Option<cmd::Command>,
+    onoff: Option<cmd::Command>,
+    function_set: Option<cmd::Command>,
+    /// Number of positions the display is shifted to the right
+    shift: u8,
+}
+
+/// **CGRAM** slots that have to be uploaded, as computed by [`Driver::sync_cgram`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CgramPlan(u8);
+
+impl CgramPlan {
+    /// Checks whether the given slot has to be uploaded
+    pub fn contains(self, slot: u8) -> bool {
+        slot < 8 && self.0 >> slot & 1 != 0
+    }
+
+    /// Slots that have to be uploaded, in ascending order
+    pub fn slots(self) -> impl Iterator<Item = u8> {
+        (0..8).filter(move |&slot| self.contains(slot))
+    }
+
+    /// Number of slots that have to be uploaded
+    pub fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    /// Checks whether the **CGRAM** is already up to date
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+}
+
+/// Operations supported by the [`Pins`] of a [`Driver`], see [`Driver::capabilities`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Capabilities {
+    /// The display can be read (see [`Pins::can_read`]), so the reads
+    /// and the polling of the busy flag are supported
+    pub can_read: bool,
+}
+
+/// Error returned by the operations that the [`Pins`] don't support
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Unsupported;
+
+/// Results of the checks done by [`Driver::self_test`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SelfTest {
+    /// A pattern written in the **DDRAM** was read back unchanged
+    pub bus: bool,
+    /// A pattern written in the **CGRAM** was read back unchanged
+    pub cgram: bool,
+    /// The busy flag was set by a [`Clear`](cmd::Command::Clear) and then went down
+    pub busy_flag: bool,
+}
+
+impl SelfTest {
+    /// Whether all the checks passed
+    pub fn passed(self) -> bool {
+        self.bus && self.cgram && self.busy_flag
+    }
+}
+
+/// Maximum number of writes that can be queued, which is the size of the **DDRAM**
+const QUEUE_SIZE: usize = 80;
+
 /// An **ST7066U** based LCD driver
-pub struct Driver<Pins: self::Pins> {
+///
+/// The `'h` lifetime is the one of the hook set by [`on_command`](Self::on_command)
+pub struct Driver<'h, Pins: self::Pins> {
     pins: Pins,
     delay: Delay,
+    /// Frequency of the CPU in MHz, used to wait for less than a µs
+    cpu_mhz: u32,
+    timing: Timing,
+    geometry: Geometry,
+    queue: heapless::Vec<(u8, u8), QUEUE_SIZE>,
+    state: State,
+    /// State to restore when waking up, present only while sleeping
+    asleep: Option<State>,
+    controller: Controller,
+    /// Whether the data pins were last used to read
+    bus_input: bool,
+    /// Number of times the data pins switched between reading and writing
+    bus_switches: u32,
+    /// Whether the bytes are transferred as two nibbles on the `D7..D4` lines,
+    /// see [`four_bit_handshake`](Self::four_bit_handshake)
+    four_bit: bool,
+    /// Durations measured for each kind of operation
+    perf: PerfStats,
+    /// Hook called with each command sent to the display
+    on_command: Option<&'h mut dyn FnMut(cmd::Command)>,
+    /// Whether the busy flag is polled instead of waiting the [`Timing`]
+    busy_polling: bool,
+    learned: Learned,
 }
 
-impl<Pins: self::Pins> Driver<Pins> {
+impl<'h, Pins: self::Pins> Driver<'h, Pins> {
     /// Sets up the [`Driver`] pins
     ///
     /// At the start all of the pins are set to output mode,
     /// and they are kept at their default level.
     /// Only the enable pin is set to low explicitly.
-    pub fn setup(mut pins: Pins, clocks: &Clocks<'_>) -> Self {
+    pub fn setup(mut pins: Pins, geometry: Geometry, clocks: &Clocks<'_>) -> Self {
         pins.set_en(false);
+        pins.set_en2(false);
         let delay = Delay::new(clocks);
-        Self { pins, delay }
+        let timing = Timing::default();
+        Self {
+            pins,
+            delay,
+            cpu_mhz: clocks.cpu_clock.to_MHz(),
+            timing,
+            geometry,
+            queue: heapless::Vec::new(),
+            state: State::default(),
+            asleep: None,
+            controller: Controller::default(),
+            bus_input: false,
+            bus_switches: 0,
+            four_bit: false,
+            perf: PerfStats::default(),
+            on_command: None,
+            busy_polling: false,
+            learned: Learned::default(),
+        }
+    }
+
+    /// Returns the [`Geometry`] of the display
+    pub fn geometry(&self) -> Geometry {
+        self.geometry
+    }
+
+    /// Returns the [`Pins`] of the display, to inspect the [`Simulator`] in the tests
+    #[cfg(test)]
+    pub(crate) fn pins(&mut self) -> &mut Pins {
+        &mut self.pins
+    }
+
+    /// Returns the operations supported by the [`Pins`]
+    pub fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            can_read: self.pins.can_read(),
+        }
+    }
+
+    /// Selects the controller targeted by the following operations
+    ///
+    /// This only matters for the displays driven by two controllers, where
+    /// [`set_ddram_line`](Self::set_ddram_line) already selects the controller
+    /// of the given line. The commands that change the state of the display are
+    /// always sent to both, and [`CgRamAddress`](cmd::Command::CgRamAddress)
+    /// selects [`Controller::Both`] so that the glyphs are the same on both.
+    pub fn select(&mut self, controller: Controller) {
+        self.controller = controller;
+    }
+
+    /// Sets a hook called with each command right before it's sent
+    ///
+    /// This allows to observe the stream of commands, for example
+    /// to log or record it, while driving a real display.
+    /// The commands skipped by [`exec`](Self::exec) are not reported.
+    ///
+    /// The hook is borrowed for as long as the [`Driver`] keeps it,
+    /// so it can capture the state it records the commands in.
+    pub fn on_command(&mut self, hook: Option<&'h mut dyn FnMut(cmd::Command)>) {
+        self.on_command = hook;
+    }
+
+    /// Returns the [`Timing`] currently used by the [`Driver`]
+    pub fn timing(&self) -> Timing {
+        self.timing
+    }
+
+    /// Replaces the [`Timing`] used by the [`Driver`]
+    ///
+    /// This allows to tune the waits for the connected display,
+    /// for example when it's faster than the worst case of the datasheet.
+    /// The enable pulses shorter than [`ENABLE_PULSE_NS`](cmd::ENABLE_PULSE_NS)
+    /// are out of spec for both reads and writes, so they are raised to it.
+    pub fn set_timing(&mut self, timing: Timing) {
+        self.timing = timing.within_spec();
+    }
+
+    /// Enables or disables the polling of the busy flag
+    ///
+    /// While polling, each operation waits only until the busy flag goes down,
+    /// and the longest time it took for each kind of operation is remembered.
+    /// When polling is disabled the [`learned_timing`](Self::learned_timing)
+    /// replaces the current one, so that the fixed waits are tuned to the
+    /// connected display.
+    ///
+    /// **Note** that this requires the display to be initialized
+    /// (see [`init_sequence`](Self::init_sequence))
+    pub fn set_busy_polling(&mut self, enabled: bool) {
+        if self.busy_polling && !enabled {
+            self.timing = self.learned_timing();
+        }
+        self.busy_polling = enabled;
+    }
+
+    /// Returns the [`Timing`] learned while polling the busy flag
+    ///
+    /// Each kind of operation observed while [polling](Self::set_busy_polling)
+    /// takes the longest time it was seen busy (see [`elapsed_us`](Self::elapsed_us)),
+    /// the others keep the current value.
+    pub fn learned_timing(&self) -> Timing {
+        let Learned {
+            long_us,
+            short_us,
+            data_us,
+        } = self.learned;
+        Timing {
+            long_us: long_us.unwrap_or(self.timing.long_us),
+            short_us: short_us.unwrap_or(self.timing.short_us),
+            data_us: data_us.unwrap_or(self.timing.data_us),
+            ..self.timing
+        }
+    }
+
+    /// Waits for the completion of an operation that takes at most `us`
+    ///
+    /// When [polling](Self::set_busy_polling) the wait ends as soon as the
+    /// busy flag goes down, and the time it took is recorded in the
+    /// [`Learned`] field selected by `kind`. If the busy flag doesn't go
+    /// down in a reasonable amount of time nothing is recorded.
+    fn wait_done(&mut self, us: u32, kind: fn(&mut Learned) -> &mut Option<u32>) {
+        if !self.busy_polling || !self.pins.can_read() {
+            self.delay.delay(us);
+            return;
+        }
+
+        let Some((elapsed, _)) = self.poll_busy(10 * us) else {
+            return;
+        };
+        let max = kind(&mut self.learned);
+        *max = Some(max.map_or(elapsed, |max| max.max(elapsed)));
     }
 
     /// Executes the given [`Command`](cmd::Command)
+    ///
+    /// With the `methods` feature (enabled by default) each command
+    /// also has its own shorthand method (e.g. [`Driver::clear`]).
+    ///
+    /// The commands that only set the state of the display
+    /// ([`EntryMode`](cmd::Command::EntryMode), [`Onoff`](cmd::Command::Onoff)
+    /// and [`FunctionSet`](cmd::Command::FunctionSet)) are skipped entirely
+    /// when they are the same as the last one sent.
+    ///
+    /// Since some controllers reset the display control when receiving a
+    /// [`FunctionSet`](cmd::Command::FunctionSet), the last
+    /// [`Onoff`](cmd::Command::Onoff) and [`EntryMode`](cmd::Command::EntryMode)
+    /// sent are sent again right after it, so that the display doesn't go blank.
     pub fn exec(&mut self, cmd: cmd::Command) {
+        if !self.track(cmd) {
+            return;
+        }
+        self.run(cmd);
+
+        if let cmd::Command::FunctionSet { .. } = cmd {
+            let State {
+                onoff, entry_mode, ..
+            } = self.state;
+            for cmd in [onoff, entry_mode].into_iter().flatten() {
+                self.run(cmd);
+            }
+        }
+    }
+
+    /// Sends the given [`Command`](cmd::Command) and waits for its completion
+    ///
+    /// Unlike [`exec`](Self::exec) the cached state is not checked nor updated
+    fn run(&mut self, cmd: cmd::Command) {
+        let start = get_cycle_count();
+        self.send(cmd);
+
+        if cmd.is_long() {
+            self.wait_done(self.timing.long_us, |learned| &mut learned.long_us);
+        } else {
+            self.wait_done(self.timing.short_us, |learned| &mut learned.short_us);
+        }
+
+        let elapsed = self.elapsed_us(start);
+        let stats = if cmd.is_long() {
+            &mut self.perf.long
+        } else {
+            &mut self.perf.short
+        };
+        stats.record(elapsed);
+    }
+
+    /// Returns the durations measured for each kind of operation
+    ///
+    /// Only the operations done through [`exec`](Self::exec),
+    /// [`write`](Self::write) and [`read`](Self::read) are measured
+    pub fn perf_stats(&self) -> &PerfStats {
+        &self.perf
+    }
+
+    /// Updates the cached state of the display with the given command
+    ///
+    /// Returns `false` if the command would leave the state unchanged,
+    /// which can only happen for the commands that have no other effect
+    fn track(&mut self, cmd: cmd::Command) -> bool {
+        use cmd::{Command::*, Direction, Shift::Display};
+        let cached = match cmd {
+            // NOTE:
+            // Clearing the display also sets the cursor to move to the right
+            Clear() => {
+                if let Some(EntryMode { cursor, .. }) = &mut self.state.entry_mode {
+                    *cursor = Direction::Right;
+                }
+                self.state.shift = 0;
+                return true;
+            }
+            ReturnHome() => {
+                self.state.shift = 0;
+                return true;
+            }
+            Shift(Display(direction)) => {
+                let len = self.shift_length();
+                self.state.shift = match direction {
+                    Direction::Right => (self.state.shift + 1) % len,
+                    Direction::Left => (self.state.shift + len - 1) % len,
+                };
+                return true;
+            }
+            EntryMode { .. } => &mut self.state.entry_mode,
+            Onoff { .. } => &mut self.state.onoff,
+            FunctionSet { .. } => &mut self.state.function_set,
+            _ => return true,
+        };
+        replace(cached, Some(cmd)) != Some(cmd)
+    }
+
+    /// Number of positions the display is currently shifted to the right
+    ///
+    /// This only accounts for the shifts done with [`Shift`](cmd::Command::Shift)
+    /// and not for the ones done while writing
+    /// (see [`EntryMode`](cmd::Command::EntryMode)).
+    pub fn display_shift(&self) -> u8 {
+        self.state.shift
+    }
+
+    /// Shifts the display so that it's shifted to the right by `offset` positions
+    ///
+    /// The display is shifted in the direction that takes the least commands,
+    /// taking into account that it wraps around after the end of the line.
+    pub fn scroll_display_to(&mut self, offset: u8) {
+        use cmd::{Direction, Shift::Display};
+        let len = self.shift_length();
+        let right = (offset % len + len - self.state.shift) % len;
+        let (direction, count) = if right <= len - right {
+            (Direction::Right, right)
+        } else {
+            (Direction::Left, len - right)
+        };
+        for _ in 0..count {
+            self.exec(cmd::Command::Shift(Display(direction)));
+        }
+    }
+
+    /// Scrolls the text on the display by one position in the given direction
+    ///
+    /// This relies on the shift of the display, so the **DDRAM** is never
+    /// rewritten and a single command is sent for each step, but only the
+    /// text that fits in a line of the **DDRAM** can be scrolled this way.
+    /// Returns the new [`display_shift`](Self::display_shift).
+    pub fn marquee_step(&mut self, dir: cmd::Direction) -> u8 {
+        self.exec(cmd::Command::Shift(cmd::Shift::Display(dir)));
+        self.state.shift
+    }
+
+    /// Number of positions after which shifting the display wraps around,
+    /// which is the length of a line in the **DDRAM**
+    fn shift_length(&self) -> u8 {
+        let lines = match self.state.function_set {
+            Some(cmd::Command::FunctionSet { lines, .. }) => lines,
+            _ if self.geometry.lines > 1 => cmd::Lines::Two,
+            _ => cmd::Lines::One,
+        };
+        match lines {
+            cmd::Lines::One => 80,
+            cmd::Lines::Two => 40,
+        }
+    }
+
+    /// Sets the color of the backlight (see [`Pins::set_backlight_color`])
+    ///
+    /// This is meant to be called each frame with the color of the
+    /// [`Canvas::backlight`](super::Canvas::backlight) animation
+    pub fn set_backlight_color(&mut self, rgb: [u8; 3]) {
+        self.pins.set_backlight_color(rgb);
+    }
+
+    /// Resets the controller through its reset pin (see [`Pins::set_reset`])
+    ///
+    /// This is more reliable than the reset by instruction, and should be
+    /// followed by the [`init_sequence`](Self::init_sequence), which also
+    /// waits for the controller to be ready.
+    pub fn hardware_reset(&mut self) {
+        self.pins.set_reset(true);
+        self.delay.delay(cmd::RESET_PULSE_US);
+        self.pins.set_reset(false);
+        self.state = State::default();
+    }
+
+    /// Resets the controller by instruction and sets its functioning mode
+    ///
+    /// This is the initialization sequence from the datasheet, needed when
+    /// the power supply conditions didn't let the internal reset circuit work
+    pub fn init_sequence(&mut self, lines: cmd::Lines, font: cmd::Font) {
+        self.state = State::default();
+        self.four_bit = false;
+        self.delay.delay(40_000);
+        for (byte, us) in cmd::EIGHT_BIT_HANDSHAKE {
+            self.transfer(false, byte, Controller::Both);
+            self.delay.delay(us);
+        }
+        self.exec(cmd::Command::FunctionSet { lines, font });
+    }
+
+    /// Resets the controller by instruction and switches it to the 4-bit interface
+    ///
+    /// The nibbles of [`FOUR_BIT_HANDSHAKE`](cmd::FOUR_BIT_HANDSHAKE) are
+    /// sent with [`write_nibble`](Self::write_nibble).
+    ///
+    /// From then on each byte is transferred as two nibbles on the `D7..D4`
+    /// lines, the most significant one first, and the
+    /// [`FunctionSet`](cmd::Command::FunctionSet) commands keep the 4-bit
+    /// interface, so this is meant for the displays wired with 4 data lines.
+    /// The functioning mode has still to be set, like after the handshake of
+    /// the [`init_sequence`](Self::init_sequence).
+    pub fn four_bit_handshake(&mut self) {
+        self.state = State::default();
+        self.four_bit = false;
+        self.delay.delay(40_000);
+        for (nibble, us) in cmd::FOUR_BIT_HANDSHAKE {
+            self.write_nibble(nibble);
+            self.delay.delay(us);
+        }
+        self.four_bit = true;
+    }
+
+    /// Writes a single nibble to the _Instruction Register_
+    ///
+    /// The nibble is placed on the `D7..D4` lines, which are the
+    /// only ones connected when using the 4-bit interface
+    pub fn write_nibble(&mut self, nibble: u8) {
         self.pins.set_rs(false);
         self.pins.set_rw(false);
-        self.pins.write(cmd.bits());
-        self.pins.set_en(true);
-        self.pins.set_en(false);
+        self.write_pulse(nibble << 4, Controller::Both);
+    }
+
+    /// Turns off the display and the backlight
+    ///
+    /// The contents of the **DDRAM** are preserved and shown
+    /// again once [`wake`](Self::wake) is called
+    pub fn sleep(&mut self) {
+        if self.asleep.is_some() {
+            return;
+        }
+        self.asleep = Some(self.state);
+        self.exec(cmd::Command::Onoff {
+            display: false,
+            cursor: false,
+            blink: false,
+        });
+        self.pins.set_backlight(false);
+    }
+
+    /// Restores the display as it was before calling [`sleep`](Self::sleep)
+    ///
+    /// The last [`EntryMode`](cmd::Command::EntryMode) and
+    /// [`Onoff`](cmd::Command::Onoff) commands are sent again
+    /// without the need of a full initialization.
+    /// If no [`Onoff`](cmd::Command::Onoff) command was ever sent
+    /// the display is turned on, without cursor
+    pub fn wake(&mut self) {
+        let Some(state) = self.asleep.take() else {
+            return;
+        };
+        self.pins.set_backlight(true);
+        if let Some(entry_mode) = state.entry_mode {
+            self.exec(entry_mode);
+        }
+        self.exec(state.onoff.unwrap_or(cmd::Command::Onoff {
+            display: true,
+            cursor: false,
+            blink: false,
+        }));
+    }
+
+    /// Sends the given [`Command`](cmd::Command) without waiting for its completion
+    fn send(&mut self, cmd: cmd::Command) {
+        use cmd::{Command::*, Shift::Cursor};
+        if let Some(hook) = &mut self.on_command {
+            hook(cmd);
+        }
+        let to = match cmd {
+            // NOTE:
+            // A DDRAM address alone refers to the first controller,
+            // unless another one was explicitly selected
+            DdRamAddress(_) | Shift(Cursor(_)) => {
+                if self.controller == Controller::Both {
+                    self.controller = Controller::First;
+                }
+                self.controller
+            }
+            CgRamAddress(_) => {
+                self.controller = Controller::Both;
+                Controller::Both
+            }
+            _ => Controller::Both,
+        };
+        let bits = match cmd {
+            // NOTE:
+            // The interface data length bit would switch back to 8 bits
+            FunctionSet { .. } if self.four_bit => cmd.bits() & !0b00010000,
+            _ => cmd.bits(),
+        };
+        self.transfer(false, bits, to);
+    }
+
+    /// Writes a byte to either the _Instruction Register_ or the _Data Register_
+    ///
+    /// The value of `rs` is the one of the register select pin (see [`Pins::set_rs`])
+    fn transfer(&mut self, rs: bool, value: u8, to: Controller) {
+        self.pins.set_rs(rs);
+        self.pins.set_rw(false);
+        if self.four_bit {
+            self.write_pulse(value & 0xf0, to);
+            self.write_pulse(value << 4, to);
+        } else {
+            self.write_pulse(value, to);
+        }
+    }
+
+    /// Places the value on the data pins and latches it with an enable pulse
+    fn write_pulse(&mut self, value: u8, to: Controller) {
+        self.bus_write(value);
+        self.enable(true, to);
+        self.delay_ns(self.timing.write_pulse_ns);
+        self.enable(false, to);
+        // NOTE:
+        // The next operation may change the bus right away
+        self.delay_ns(self.timing.write_hold_ns);
+    }
+
+    /// Writes the value on the data pins, keeping count of the direction switches
+    fn bus_write(&mut self, value: u8) {
+        if replace(&mut self.bus_input, false) {
+            self.bus_switches = self.bus_switches.wrapping_add(1);
+        }
+        self.pins.write(value);
+    }
+
+    /// Reads the value of the data pins, keeping count of the direction switches
+    fn bus_read(&mut self) -> u8 {
+        if !replace(&mut self.bus_input, true) {
+            self.bus_switches = self.bus_switches.wrapping_add(1);
+        }
+        self.pins.read()
+    }
+
+    /// Number of times the data pins switched between writing and reading
+    ///
+    /// Switching the direction of the pins is costly on most buses,
+    /// so this can be used to find out when reordering the reads and
+    /// the writes would pay off. The counter wraps around on overflow.
+    pub fn bus_switches(&self) -> u32 {
+        self.bus_switches
+    }
+
+    /// Returns the µs elapsed since `start`, a value of the CPU cycle counter
+    ///
+    /// The operations of the display take tens of µs, way less than the
+    /// [`PRECISION`](crate::timer::PRECISION) of the [`timer`](crate::timer),
+    /// so they are measured in CPU cycles. The result is rounded up.
+    fn elapsed_us(&self, start: u32) -> u32 {
+        let cycles = get_cycle_count().wrapping_sub(start);
+        cycles.saturating_add(self.cpu_mhz - 1) / self.cpu_mhz
+    }
+
+    /// Busy waits for at least `ns` nanoseconds
+    ///
+    /// The waits of the bus transactions are way shorter than a µs,
+    /// so they are done by counting the CPU cycles instead of using
+    /// the [`Delay`], which would round them up.
+    fn delay_ns(&self, ns: u32) {
+        // NOTE:
+        // The product of two `u32` can overflow, while it always fits a `u64`
+        let cycles = (u64::from(ns) * u64::from(self.cpu_mhz) + 999) / 1000;
+        let start = get_cycle_count();
+        while u64::from(get_cycle_count().wrapping_sub(start)) < cycles {}
+    }
+
+    /// Sets the enable pins of the given controllers
+    fn enable(&mut self, value: bool, to: Controller) {
+        if to != Controller::Second {
+            self.pins.set_en(value);
+        }
+        if to != Controller::First && self.geometry.controllers() == 2 {
+            self.pins.set_en2(value);
+        }
+    }
+
+    /// Returns the controller to read from
+    fn reading(&self) -> Controller {
+        match self.controller {
+            Controller::Both => Controller::First,
+            controller => controller,
+        }
+    }
+
+    /// Writes an arbitrary byte to the _Instruction Register_
+    ///
+    /// This bypasses [`Command`](cmd::Command) and can be used to access
+    /// instructions that are not part of the **ST7066U** command set,
+    /// the [`Driver`] then waits [`Timing::short_us`].
+    /// Like the other commands, it's sent to all the controllers.
+    ///
+    /// Since the byte could change the state of the display in any way,
+    /// the state cached by the [`Driver`] is forgotten.
+    pub fn raw_instruction(&mut self, byte: u8) {
+        self.state = State::default();
+        self.transfer(false, byte, Controller::Both);
+        self.delay.delay(self.timing.short_us);
+    }
+
+    /// Writes an arbitrary byte to the _Data Register_
+    ///
+    /// This is the counterpart of [`raw_instruction`](Self::raw_instruction),
+    /// and behaves just like [`write`](Self::write)
+    pub fn raw_data(&mut self, byte: u8) {
+        self.transfer(true, byte, self.controller);
+        self.delay.delay(self.timing.data_us);
+    }
+
+    /// Measures the execution time of the long commands on the connected display
+    ///
+    /// A [`Clear`](cmd::Command::Clear) is issued and the busy flag is polled
+    /// until it goes down, the elapsed time (see [`elapsed_us`](Self::elapsed_us))
+    /// then replaces [`Timing::long_us`].
+    ///
+    /// If the busy flag is never seen set, or it doesn't go down in a reasonable
+    /// amount of time, the display is not considered reliable and the current
+    /// timing is kept.
+    pub fn calibrate(&mut self) {
+        let timeout = 10 * Timing::default().long_us;
+
+        self.track(cmd::Command::Clear());
+        self.send(cmd::Command::Clear());
+
+        match self.poll_busy(timeout) {
+            Some((elapsed, true)) => self.timing.long_us = elapsed,
+            _ => self.delay.delay(self.timing.long_us),
+        }
+    }
 
-        use cmd::Command::*;
-        let us = match cmd {
-            Clear() => 1600,
-            ReturnHome() => 1600,
-            EntryMode { .. } => 40,
-            Onoff { .. } => 40,
-            Shift(_) => 40,
-            FunctionSet { .. } => 40,
-            CgRamAddress(_) => 40,
-            DdRamAddress(_) => 40,
+    /// Initializes the display and checks that it works as expected
+    ///
+    /// After the [`init_sequence`](Self::init_sequence) a known pattern is
+    /// written and read back from both the **DDRAM** and the first slot of
+    /// the **CGRAM**, and the busy flag is checked to go up and down during a
+    /// [`Clear`](cmd::Command::Clear). The display is left cleared, but the
+    /// first slot of the **CGRAM** is overwritten.
+    pub fn self_test(&mut self) -> SelfTest {
+        const PATTERN: [u8; 8] = [
+            0b10101, 0b01010, 0b10101, 0b01010, 0b10101, 0b01010, 0b10101, 0b01010,
+        ];
+        let lines = match self.geometry.lines {
+            1 => cmd::Lines::One,
+            _ => cmd::Lines::Two,
         };
-        self.delay.delay(us);
+        self.init_sequence(lines, cmd::Font::Size5x8);
+
+        // NOTE:
+        // Alternating the bits of the whole byte checks every data line
+        let bytes = PATTERN.map(|v| if v & 1 != 0 { 0x55 } else { 0xaa });
+        let columns = usize::from(self.geometry.columns).min(bytes.len());
+        self.set_ddram_line(0);
+        bytes[..columns].iter().for_each(|&v| self.write(v));
+        self.set_ddram_line(0);
+        let bus = bytes[..columns].iter().all(|&v| self.read() == Ok(v));
+
+        self.exec(cmd::Command::CgRamAddress(0));
+        PATTERN.into_iter().for_each(|v| self.write(v));
+        self.exec(cmd::Command::CgRamAddress(0));
+        let cgram = PATTERN
+            .into_iter()
+            .all(|v| self.read().map(|read| read & 0b11111) == Ok(v));
+
+        let timeout = 10 * Timing::default().long_us;
+        self.track(cmd::Command::Clear());
+        self.send(cmd::Command::Clear());
+        let busy_flag = matches!(self.poll_busy(timeout), Some((_, true)));
+        self.delay.delay(self.timing.long_us);
+
+        SelfTest {
+            bus,
+            cgram,
+            busy_flag,
+        }
     }
 
     /// Writes a byte to the [`Driver`]
@@ -109,17 +945,352 @@ impl<Pins: self::Pins> Driver<Pins> {
     /// [`DdramAddress`](Command::DdramAddress) this function
     /// will write either to the **CGRAM** or to the **DDRAM**, respectively.
     pub fn write(&mut self, value: u8) {
-        self.pins.set_rs(true);
-        self.pins.set_rw(false);
-        self.pins.write(value);
-        self.pins.set_en(true);
-        self.pins.set_en(false);
-        self.delay.delay(37);
+        let start = get_cycle_count();
+        self.transfer(true, value, self.controller);
+        self.wait_done(self.timing.data_us, |learned| &mut learned.data_us);
+        let elapsed = self.elapsed_us(start);
+        self.perf.write.record(elapsed);
+    }
+
+    /// Moves the **AC** to the first character of the given line
+    ///
+    /// The address, and the controller to select, are computed
+    /// from the [`Geometry`] of the display
+    pub fn set_ddram_line(&mut self, line: u8) {
+        self.controller = self.geometry.controller_of(line);
+        let address = self.geometry.line_address(line);
+        self.exec(cmd::Command::DdRamAddress(address));
+    }
+
+    /// Reads the characters shown on the given line of the display
+    ///
+    /// At most one character for each column is read, and the characters
+    /// that can't be decoded (like the ones of the **CGRAM**) are replaced
+    /// by [`char::REPLACEMENT_CHARACTER`].
+    ///
+    /// Returns the number of characters written in `out`
+    pub fn read_line_text(&mut self, line: u8, out: &mut [char]) -> Result<usize, Unsupported> {
+        self.set_ddram_line(line);
+        let len = out.len().min(usize::from(self.geometry.columns));
+        for ch in &mut out[..len] {
+            let code = self.read()?;
+            *ch = ddrom::decode(code).unwrap_or(char::REPLACEMENT_CHARACTER);
+        }
+        Ok(len)
+    }
+
+    /// Writes each string on its own line of the display
+    ///
+    /// Each string is truncated or padded with spaces to fill the whole line,
+    /// and the characters that are not in the **DDROM** are replaced by spaces.
+    /// The strings in excess of the number of lines are ignored.
+    ///
+    /// **Note** that this expects the cursor to be moving to the right
+    /// (see [`EntryMode`](cmd::Command::EntryMode))
+    pub fn write_lines(&mut self, lines: &[&str]) {
+        for (line, text) in (0..self.geometry.lines).zip(lines) {
+            self.set_ddram_line(line);
+            let mut chars = text.chars();
+            for _ in 0..self.geometry.columns {
+                let ch = chars.next().and_then(ddrom::encode);
+                self.write(ch.unwrap_or(b' '));
+            }
+        }
+    }
+
+    /// Queues the write of a byte to the given **DDRAM** address
+    ///
+    /// Nothing is sent to the display until [`flush`](Self::flush) is called,
+    /// unless the queue is full, in which case it's flushed right away.
+    /// Queueing another write to the same address replaces the previous one.
+    pub fn queue_write(&mut self, address: u8, value: u8) {
+        if let Some(queued) = self.queue.iter_mut().find(|(a, _)| *a == address) {
+            queued.1 = value;
+            return;
+        }
+        if self.queue.is_full() {
+            self.flush();
+        }
+        // NOTE:
+        // The queue was just flushed if it was full, so this can't fail
+        self.queue.push((address, value)).unwrap();
+    }
+
+    /// Sends all the queued writes to the display
+    ///
+    /// The writes are sorted by address so that the address is set only
+    /// at the start of each run of contiguous addresses.
+    ///
+    /// **Note** that this expects the cursor to be moving to the right
+    /// (see [`EntryMode`](cmd::Command::EntryMode))
+    pub fn flush(&mut self) {
+        let mut queue = core::mem::take(&mut self.queue);
+        queue.sort_unstable_by_key(|&(address, _)| address);
+
+        let mut next = None;
+        for (address, value) in queue {
+            if next != Some(address) {
+                self.exec(cmd::Command::DdRamAddress(address));
+            }
+            self.write(value);
+            // NOTE:
+            // Nothing can follow the last address
+            next = address.checked_add(1);
+        }
+    }
+
+    /// Updates the display from the `old` frame to the `new` one
+    ///
+    /// The frames are the ones produced by [`Canvas::render`](super::canvas::Canvas::render),
+    /// and only the **CGRAM** slots and the **DDRAM** cells that differ are written.
+    /// When `old` is [`None`] the whole frame is written.
+    ///
+    /// After the **CGRAM** is written the **AC** points to it, so the **DDRAM**
+    /// address is always set once before the first **DDRAM** write, and then
+    /// again only at the start of each run of contiguous addresses.
+    ///
+    /// **Note** that this expects the cursor to be moving to the right
+    /// (see [`EntryMode`](cmd::Command::EntryMode))
+    pub fn present_frame(&mut self, old: Option<&(DdRam, CgRam)>, new: &(DdRam, CgRam)) {
+        let (ddram, cgram) = new;
+
+        let mut next = None;
+        for (slot, glyph) in cgram.iter().enumerate() {
+            if old.and_then(|(_, old)| old.get(slot)) == Some(glyph) {
+                continue;
+            }
+            if next != Some(slot) {
+                self.exec(cmd::Command::CgRamAddress(slot as u8 * 8));
+            }
+            glyph.iter().for_each(|&v| self.write(v));
+            next = Some(slot + 1);
+        }
+
+        // NOTE:
+        // Starting from `None` makes the first write set the DDRAM address,
+        // whatever the AC was pointing to
+        let mut next = None;
+        for (i, &value) in ddram.iter().enumerate() {
+            if old.map(|(old, _)| old[i]) == Some(value) {
+                continue;
+            }
+            let address = ddram_address(i);
+            if next != Some(address) {
+                self.exec(cmd::Command::DdRamAddress(address));
+            }
+            self.write(value);
+            next = Some(address + 1);
+        }
+    }
+
+    /// Writes all the glyphs of the given **CGRAM** starting from the first slot
+    ///
+    /// The glyphs are written in a single run relying on the auto-increment
+    /// of the **AC**, which is left pointing to the **CGRAM**.
+    ///
+    /// **Note** that this expects the cursor to be moving to the right
+    /// (see [`EntryMode`](cmd::Command::EntryMode))
+    pub fn upload_cgram(&mut self, cgram: &CgRam) {
+        self.exec(cmd::Command::CgRamAddress(0));
+        cgram.iter().flatten().for_each(|&v| self.write(v));
+    }
+
+    /// Writes the glyph in the given **CGRAM** slot and shows it at the given **DDRAM** address
+    ///
+    /// The slot is one of the first 8, and its code is the one written in the
+    /// **DDRAM**. The **AC** is left pointing to the **DDRAM**, right after
+    /// the glyph, so the following writes continue the text.
+    ///
+    /// **Note** that this expects the cursor to be moving to the right
+    /// (see [`EntryMode`](cmd::Command::EntryMode))
+    pub fn show_custom(&mut self, slot: u8, bitmap: Bitmap, ddram_addr: u8) {
+        let slot = slot % 8;
+        self.exec(cmd::Command::CgRamAddress(slot * 8));
+        bitmap.raw().into_iter().for_each(|v| self.write(v));
+        self.exec(cmd::Command::DdRamAddress(ddram_addr));
+        self.write(slot);
+    }
+
+    /// Writes the given rows in a **CGRAM** slot, starting from the given row
+    ///
+    /// This updates only part of a glyph, like the top of a rising level,
+    /// without writing the whole of it again. Nothing is written if the
+    /// slot is not one of the first 8 or if the rows don't fit in it.
+    ///
+    /// Returns whether the rows were written, the **AC** is left pointing to the **CGRAM**
+    ///
+    /// **Note** that this expects the cursor to be moving to the right
+    /// (see [`EntryMode`](cmd::Command::EntryMode))
+    pub fn write_cgram_at(&mut self, slot: u8, row: u8, bytes: &[u8]) -> bool {
+        if slot >= 8 || usize::from(row) + bytes.len() > 8 {
+            return false;
+        }
+        self.exec(cmd::Command::CgRamAddress(slot * 8 + row));
+        bytes.iter().for_each(|&v| self.write(v));
+        true
+    }
+
+    /// Compares the current contents of the **CGRAM** with the given ones
+    ///
+    /// The slots are read back from the display, so the glyphs left there
+    /// by a previous run (when the display wasn't power-cycled) are reused
+    /// instead of being uploaded again. The returned plan contains the
+    /// slots that differ, which can then be written with
+    /// [`upload_cgram_plan`](Self::upload_cgram_plan).
+    ///
+    /// Only the 5 least significant bits of each row are compared,
+    /// as the others are not part of the glyph. When the display can't be
+    /// read all the slots are considered different.
+    pub fn sync_cgram(&mut self, target: &CgRam) -> CgramPlan {
+        self.exec(cmd::Command::CgRamAddress(0));
+        let mut plan = CgramPlan::default();
+        for (slot, glyph) in target.iter().enumerate() {
+            let current: [_; 8] = from_fn(|_| self.read().map(|v| v & 0b11111));
+            if current != glyph.map(|v| Ok(v & 0b11111)) {
+                plan.0 |= 1 << slot;
+            }
+        }
+        plan
+    }
+
+    /// Uploads only the slots of the **CGRAM** contained in the plan
+    pub fn upload_cgram_plan(&mut self, cgram: &CgRam, plan: CgramPlan) {
+        for slot in plan.slots() {
+            let Some(glyph) = cgram.get(usize::from(slot)) else {
+                continue;
+            };
+            self.exec(cmd::Command::CgRamAddress(slot * 8));
+            glyph.iter().for_each(|&v| self.write(v));
+        }
+    }
+
+    /// Brings the controller back to the state cached by the [`Driver`]
+    ///
+    /// After a glitch of the power supply the controller may have lost its
+    /// state, so the last [`FunctionSet`](cmd::Command::FunctionSet),
+    /// [`Onoff`](cmd::Command::Onoff) and [`EntryMode`](cmd::Command::EntryMode)
+    /// sent are sent again, and the display is shifted back to the
+    /// [`display_shift`](Self::display_shift). The **AC** is read before
+    /// and then restored as a **DDRAM** address.
+    ///
+    /// Unlike a full [`init_sequence`](Self::init_sequence) the contents of
+    /// the **DDRAM** are kept. Returns `false`, without sending anything, if
+    /// the display can't be read or its busy flag doesn't go down in a
+    /// reasonable amount of time.
+    pub fn resync(&mut self) -> bool {
+        let timeout = 10 * Timing::default().long_us;
+        if self.poll_busy(timeout).is_none() {
+            return false;
+        }
+        let address = self.read_instruction() & 0b01111111;
+
+        let State {
+            entry_mode,
+            onoff,
+            function_set,
+            shift,
+        } = self.state;
+        for cmd in [function_set, onoff, entry_mode].into_iter().flatten() {
+            self.run(cmd);
+        }
+        self.exec(cmd::Command::ReturnHome());
+        self.scroll_display_to(shift);
+        self.exec(cmd::Command::DdRamAddress(address));
+        true
+    }
+
+    /// Checks whether the display is connected and responding
+    ///
+    /// A known pattern is written in the last **CGRAM** slot and then read back:
+    /// a disconnected display leaves the bus floating, which reads back as
+    /// constant values (e.g. all ones or all zeros) instead of the pattern.
+    ///
+    /// The previous contents of the slot are restored afterwards,
+    /// but the **AC** is left pointing to the **CGRAM**. A display
+    /// that can't be read is never considered responding.
+    pub fn probe(&mut self) -> bool {
+        const SLOT: u8 = 7 * 8;
+        const PATTERN: [u8; 8] = [
+            0b10101, 0b01010, 0b10101, 0b01010, 0b10101, 0b01010, 0b10101, 0b01010,
+        ];
+
+        if !self.pins.can_read() {
+            return false;
+        }
+
+        self.exec(cmd::Command::CgRamAddress(SLOT));
+        let saved: [_; 8] = from_fn(|_| self.read().unwrap_or_default());
+
+        self.exec(cmd::Command::CgRamAddress(SLOT));
+        PATTERN.into_iter().for_each(|v| self.write(v));
+
+        self.exec(cmd::Command::CgRamAddress(SLOT));
+        let readback: [_; 8] = from_fn(|_| self.read().map(|v| v & 0b11111));
+
+        self.exec(cmd::Command::CgRamAddress(SLOT));
+        saved.into_iter().for_each(|v| self.write(v));
+
+        readback == PATTERN.map(Ok)
     }
 
     /// Checks the busy flag to know if the [`Driver`] is executing a command
-    pub fn is_busy(&mut self) -> bool {
-        self.read_address_counter() & 0b10000000 != 0
+    ///
+    /// The data pins are switched to input only if they were used to write,
+    /// so calling this in a loop switches their direction once, and they
+    /// switch back with the next write (see [`bus_switches`](Self::bus_switches))
+    pub fn is_busy(&mut self) -> Result<bool, Unsupported> {
+        Ok(self.read_address_counter()? & 0b10000000 != 0)
+    }
+
+    /// Polls the busy flag until it goes down, or `timeout_us` elapse
+    ///
+    /// Like with [`is_busy`](Self::is_busy) the data pins are switched to
+    /// input once for the whole loop. Returns the µs elapsed and whether the
+    /// flag was ever seen set, or [`None`] on timeout and when the display
+    /// can't be read.
+    fn poll_busy(&mut self, timeout_us: u32) -> Option<(u32, bool)> {
+        if !self.pins.can_read() {
+            return None;
+        }
+        let start = get_cycle_count();
+        let mut seen_busy = false;
+        while self.read_instruction() & 0b10000000 != 0 {
+            seen_busy = true;
+            if self.elapsed_us(start) > timeout_us {
+                return None;
+            }
+        }
+        Some((self.elapsed_us(start), seen_busy))
+    }
+
+    /// Checks the busy flag reading only the `D7` line (see [`Pins::read_d7`])
+    ///
+    /// The controller drives all the data lines during a read, so if they were
+    /// used to write, and they can be read, the first poll reads them all to
+    /// switch them to input, while the following ones only read `D7`.
+    /// Returns [`None`] if the pins don't support it.
+    pub fn busy_via_d7(&mut self) -> Option<bool> {
+        self.pins.set_rs(false);
+        self.pins.set_rw(true);
+        let from = self.reading();
+        self.enable(true, from);
+        self.delay_ns(self.timing.read_pulse_ns);
+        let busy = if !self.bus_input && self.pins.can_read() {
+            Some(self.bus_read() & 0b10000000 != 0)
+        } else {
+            self.pins.read_d7()
+        };
+        self.enable(false, from);
+        self.delay_ns(cmd::ENABLE_CYCLE_NS.saturating_sub(self.timing.read_pulse_ns));
+        if self.four_bit {
+            // NOTE:
+            // The least significant nibble has to be read too
+            self.enable(true, from);
+            self.delay_ns(self.timing.read_pulse_ns);
+            self.enable(false, from);
+            self.delay_ns(cmd::ENABLE_CYCLE_NS.saturating_sub(self.timing.read_pulse_ns));
+        }
+        busy
     }
 
     /// Reads the address counter
@@ -130,14 +1301,42 @@ impl<Pins: self::Pins> Driver<Pins> {
     /// [`DdramAddress`](Command::DdramAddress) respectively.
     ///
     /// The most significant bit of the returned value is the busy flag
-    pub fn read_address_counter(&mut self) -> u8 {
+    pub fn read_address_counter(&mut self) -> Result<u8, Unsupported> {
+        if !self.pins.can_read() {
+            return Err(Unsupported);
+        }
+        Ok(self.read_instruction())
+    }
+
+    /// Reads the _Instruction Register_, which holds the busy flag and the **AC**
+    ///
+    /// The data pins are left in input mode
+    fn read_instruction(&mut self) -> u8 {
         self.pins.set_rs(false);
         self.pins.set_rw(true);
-        self.pins.write(0);
-        self.pins.set_en(true);
-        let value = self.pins.read();
-        self.pins.set_en(false);
-        self.delay.delay(1);
+        let value = self.read_transfer();
+        self.delay_ns(cmd::ENABLE_CYCLE_NS.saturating_sub(self.timing.read_pulse_ns));
+        value
+    }
+
+    /// Reads a byte from the register already selected, with one or two
+    /// enable pulses depending on the interface
+    fn read_transfer(&mut self) -> u8 {
+        if !self.four_bit {
+            return self.read_pulse();
+        }
+        let high = self.read_pulse();
+        self.delay_ns(cmd::ENABLE_CYCLE_NS.saturating_sub(self.timing.read_pulse_ns));
+        high & 0xf0 | self.read_pulse() >> 4
+    }
+
+    /// Reads the value of the data pins during an enable pulse
+    fn read_pulse(&mut self) -> u8 {
+        let from = self.reading();
+        self.enable(true, from);
+        self.delay_ns(self.timing.read_pulse_ns);
+        let value = self.bus_read();
+        self.enable(false, from);
         value
     }
 
@@ -147,13 +1346,572 @@ impl<Pins: self::Pins> Driver<Pins> {
     /// was [`CgramAddress`](Command::CgramAddress) or
     /// [`DdramAddress`](Command::DdramAddress) this function
     /// will read either from the **CGRAM** or from the **DDRAM**, respectively.
-    pub fn read(&mut self) -> u8 {
+    ///
+    /// Returns [`Unsupported`] if the display can't be read (see [`Pins::can_read`])
+    pub fn read(&mut self) -> Result<u8, Unsupported> {
+        if !self.pins.can_read() {
+            return Err(Unsupported);
+        }
+        let start = get_cycle_count();
         self.pins.set_rs(true);
         self.pins.set_rw(true);
-        self.pins.set_en(true);
-        let value = self.pins.read();
-        self.pins.set_en(false);
-        self.delay.delay(37);
-        value
+        let value = self.read_transfer();
+        self.wait_done(self.timing.data_us, |learned| &mut learned.data_us);
+        let elapsed = self.elapsed_us(start);
+        self.perf.read.record(elapsed);
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Geometry of the display of the tests, a WH1601L driven as 2 lines of 8 characters
+    const GEOMETRY: Geometry = Geometry {
+        columns: 8,
+        lines: 2,
+        pixel_pitch_x: 6,
+        pixel_pitch_y: 9,
+    };
+
+    #[test]
+    fn show_custom_uploads_the_glyph() {
+        let heart = Bitmap::new([0, 0b01010, 0b11111, 0b11111, 0b01110, 0b00100, 0, 0]);
+        let mut driver = Simulator::default().driver(GEOMETRY);
+        driver.show_custom(0, heart, 0x40);
+        assert_eq!(driver.pins().first.cgram_bitmap(0), heart);
+        assert_eq!(driver.pins().first.ddram[0x40], 0);
+    }
+
+    #[test]
+    fn write_lines_pads_each_line() {
+        let mut driver = Simulator::default().driver(GEOMETRY);
+        driver.write_lines(&["Hello, world", "Hi", "ignored"]);
+        let ddram = &driver.pins().first.ddram;
+        assert_eq!(ddram[..0x08], *b"Hello, w");
+        assert_eq!(ddram[0x08], b' ');
+        assert_eq!(ddram[0x40..0x48], *b"Hi      ");
+    }
+
+    #[test]
+    fn probe() {
+        let mut driver = Simulator::default().driver(GEOMETRY);
+        driver.pins().first.cgram[0x38..].copy_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        assert!(driver.probe());
+        assert_eq!(driver.pins().first.cgram[0x38..], [1, 2, 3, 4, 5, 6, 7, 8]);
+
+        let mut disconnected = Simulator::default();
+        disconnected.stuck = Some(0xff);
+        assert!(!disconnected.driver(GEOMETRY).probe());
+    }
+
+    #[test]
+    fn present_frame_after_cgram() {
+        let old = (DdRam::default(), CgRam::from_slice(&[[0; 8]]).unwrap());
+        let mut new = (
+            DdRam::default(),
+            CgRam::from_slice(&[[0b11111; 8]]).unwrap(),
+        );
+        new.0[9] = b'A';
+
+        let mut driver = Simulator::default().driver(GEOMETRY);
+        driver.present_frame(Some(&old), &new);
+        assert_eq!(driver.pins().first.cgram_bitmap(0).raw(), [0b11111; 8]);
+        assert_eq!(driver.pins().first.cgram_bitmap(1), Bitmap::default());
+        assert_eq!(driver.pins().first.ddram[0x41], b'A');
+    }
+
+    #[test]
+    fn on_command_records_the_commands() {
+        use cmd::{Command::*, Direction::Right, Font::Size5x8, Lines::Two};
+        let onoff = Onoff {
+            display: true,
+            cursor: false,
+            blink: false,
+        };
+        let entry_mode = EntryMode {
+            cursor: Right,
+            display: false,
+        };
+        let function_set = FunctionSet {
+            lines: Two,
+            font: Size5x8,
+        };
+
+        let mut log = heapless::Vec::<_, 8>::new();
+        let mut record = |cmd| log.push(cmd).unwrap();
+        let mut driver = Simulator::default().driver(GEOMETRY);
+        driver.on_command(Some(&mut record));
+        for cmd in [onoff, entry_mode, function_set, onoff] {
+            driver.exec(cmd);
+        }
+        drop(driver);
+        assert_eq!(log, [onoff, entry_mode, function_set, onoff, entry_mode]);
+    }
+
+    #[test]
+    fn learned_timing() {
+        let mut simulator = Simulator::default();
+        simulator.busy_us = 20;
+        let mut driver = simulator.driver(GEOMETRY);
+        let datasheet = driver.timing();
+        driver.set_busy_polling(true);
+
+        // Nothing was observed yet
+        let learned = driver.learned_timing();
+        assert_eq!(learned.long_us, datasheet.long_us);
+        assert_eq!(learned.short_us, datasheet.short_us);
+        assert_eq!(learned.data_us, datasheet.data_us);
+
+        driver.exec(cmd::Command::DdRamAddress(0));
+        driver.write(b'A');
+        let learned = driver.learned_timing();
+        assert!((20..=datasheet.short_us).contains(&learned.short_us));
+        assert!((20..=datasheet.data_us).contains(&learned.data_us));
+        assert_eq!(learned.long_us, datasheet.long_us);
+
+        driver.set_busy_polling(false);
+        assert_eq!(driver.timing().short_us, learned.short_us);
+    }
+
+    #[test]
+    fn enable_pulses_within_spec() {
+        let timing = Timing::default();
+        assert!(timing.read_pulse_ns >= cmd::READ_DELAY_NS);
+        assert!(timing.read_pulse_ns >= cmd::ENABLE_PULSE_NS);
+
+        let timing = Timing {
+            write_pulse_ns: 100,
+            read_pulse_ns: 200,
+            ..timing
+        }
+        .within_spec();
+        assert_eq!(timing.write_pulse_ns, cmd::ENABLE_PULSE_NS);
+        assert_eq!(timing.read_pulse_ns, cmd::ENABLE_PULSE_NS);
+    }
+
+    #[test]
+    fn hardware_reset_before_init() {
+        use cmd::{Font::Size5x8, Lines::Two};
+
+        let mut driver = Simulator::default().driver(GEOMETRY);
+        driver.raw_data(b'a');
+        driver.hardware_reset();
+        assert!(driver.pins().reset_us >= Some(cmd::RESET_PULSE_US));
+        assert_eq!(driver.pins().first.ddram[0], b' ');
+
+        let instructions = driver.pins().instructions;
+        driver.init_sequence(Two, Size5x8);
+        assert!(driver.pins().instructions > instructions);
+        driver.raw_data(b'b');
+        assert_eq!(driver.pins().first.ddram[0], b'b');
+    }
+
+    #[test]
+    fn marquee_step() {
+        use cmd::Direction::{Left, Right};
+
+        let mut driver = Simulator::default().driver(GEOMETRY);
+        driver.write_lines(&["Hello"]);
+        let (ddram, instructions) = (driver.pins().first.ddram, driver.pins().instructions);
+        assert_eq!(driver.marquee_step(Right), 1);
+        assert_eq!(driver.marquee_step(Right), 2);
+        assert_eq!(driver.marquee_step(Left), 1);
+        assert_eq!(driver.marquee_step(Left), 0);
+        // The display wraps around after the 40 positions of a line
+        assert_eq!(driver.marquee_step(Left), 39);
+        assert_eq!(driver.pins().instructions, instructions + 5);
+        assert_eq!(driver.pins().first.ddram, ddram);
+    }
+
+    #[test]
+    fn scroll_display_to() {
+        let mut driver = Simulator::default().driver(GEOMETRY);
+        let shifts = |driver: &mut Driver<Simulator>, offset| {
+            let instructions = driver.pins().instructions;
+            driver.scroll_display_to(offset);
+            assert_eq!(driver.display_shift(), offset);
+            driver.pins().instructions - instructions
+        };
+        driver.scroll_display_to(2);
+        assert_eq!(shifts(&mut driver, 6), 4);
+        assert_eq!(shifts(&mut driver, 2), 4);
+        // The display wraps around after the 40 positions of a line
+        assert_eq!(shifts(&mut driver, 38), 4);
+        assert_eq!(shifts(&mut driver, 38), 0);
+    }
+
+    #[test]
+    fn resync_restores_the_cached_state() {
+        let mut driver = Simulator::default().driver(GEOMETRY);
+        driver.exec(cmd::Command::Onoff {
+            display: true,
+            cursor: false,
+            blink: false,
+        });
+        driver.raw_data(b'a');
+        driver.raw_data(b'b');
+        driver.pins().first.display = false;
+        assert!(driver.resync());
+        assert!(driver.pins().first.display);
+        driver.raw_data(b'c');
+        assert_eq!(driver.pins().first.ddram[..3], *b"abc");
+
+        // A display that is always busy is not responsive
+        driver.pins().stuck = Some(0xff);
+        assert!(!driver.resync());
+    }
+
+    #[test]
+    fn sleep_and_wake() {
+        let mut driver = Simulator::default().driver(GEOMETRY);
+        driver.write_lines(&["Hi"]);
+        driver.sleep();
+        assert!(!driver.pins().first.display);
+        assert!(!driver.pins().backlight);
+        driver.wake();
+        assert!(driver.pins().first.display);
+        assert!(driver.pins().backlight);
+        assert_eq!(driver.pins().first.ddram[..2], *b"Hi");
+
+        driver.exec(cmd::Command::Onoff {
+            display: false,
+            cursor: true,
+            blink: false,
+        });
+        driver.sleep();
+        driver.wake();
+        assert!(!driver.pins().first.display);
+    }
+
+    #[test]
+    fn self_test() {
+        let mut simulator = Simulator::default();
+        simulator.busy_us = 100;
+        let mut driver = simulator.driver(GEOMETRY);
+        assert!(driver.self_test().passed());
+
+        // The busy flag is never set
+        let report = Simulator::default().driver(GEOMETRY).self_test();
+        assert_eq!(
+            report,
+            SelfTest {
+                bus: true,
+                cgram: true,
+                busy_flag: false
+            }
+        );
+
+        let mut stuck = Simulator::default();
+        stuck.stuck = Some(0x00);
+        let report = stuck.driver(GEOMETRY).self_test();
+        assert_eq!(report, SelfTest::default());
+    }
+
+    #[test]
+    fn calibrate() {
+        let mut simulator = Simulator::default();
+        simulator.busy_us = 1000;
+        let mut driver = simulator.driver(GEOMETRY);
+        driver.calibrate();
+        let long_us = driver.timing().long_us;
+        assert!((1000..1010).contains(&long_us), "{long_us}");
+
+        // Without the busy flag the timing is kept
+        let mut driver = Simulator::default().driver(GEOMETRY);
+        driver.calibrate();
+        assert_eq!(driver.timing().long_us, Timing::default().long_us);
+    }
+
+    #[test]
+    fn two_controllers() {
+        let geometry = Geometry {
+            columns: 40,
+            lines: 4,
+            ..GEOMETRY
+        };
+        let mut driver = Simulator::default().driver(geometry);
+        driver.write_lines(&["one", "two", "three", "four"]);
+        let pins = driver.pins();
+        assert_eq!(pins.first.ddram[..3], *b"one");
+        assert_eq!(pins.first.ddram[0x40..0x43], *b"two");
+        assert_eq!(pins.second.ddram[..5], *b"three");
+        assert_eq!(pins.second.ddram[0x40..0x44], *b"four");
+
+        driver.raw_instruction(0b00001100);
+        assert!(driver.pins().first.display);
+        assert!(driver.pins().second.display);
+    }
+
+    #[test]
+    fn four_bit_handshake() {
+        let mut driver = Simulator::default().driver(GEOMETRY);
+        // Only the last nibble of the handshake switches to the 4-bit interface
+        for (i, (nibble, _)) in cmd::FOUR_BIT_HANDSHAKE.into_iter().enumerate() {
+            assert!(!driver.pins().first.four_bit, "nibble {i}");
+            driver.write_nibble(nibble);
+        }
+        assert!(driver.pins().first.four_bit);
+        assert_eq!(driver.pins().instructions, 4);
+
+        let mut driver = Simulator::default().driver(GEOMETRY);
+        driver.four_bit_handshake();
+        driver.exec(cmd::Command::FunctionSet {
+            lines: cmd::Lines::Two,
+            font: cmd::Font::Size5x8,
+        });
+        driver.write_lines(&["Hello", "world"]);
+        assert!(driver.pins().first.four_bit);
+        assert_eq!(driver.pins().first.ddram[..5], *b"Hello");
+        assert_eq!(driver.pins().first.ddram[0x40..0x45], *b"world");
+
+        driver.set_ddram_line(1);
+        assert_eq!(driver.read(), Ok(b'w'));
+        assert_eq!(driver.read_address_counter(), Ok(0x41));
+    }
+
+    #[test]
+    fn bus_switches() {
+        let mut driver = Simulator::default().driver(GEOMETRY);
+        let switches = driver.bus_switches();
+        b"abc".iter().for_each(|&v| driver.raw_data(v));
+        assert_eq!(driver.bus_switches(), switches);
+        driver.exec(cmd::Command::DdRamAddress(0));
+        assert_eq!(driver.read(), Ok(b'a'));
+        assert_eq!(driver.read(), Ok(b'b'));
+        assert_eq!(driver.bus_switches(), switches + 1);
+        driver.raw_data(b'd');
+        assert_eq!(driver.bus_switches(), switches + 2);
+    }
+
+    #[test]
+    fn busy_polls_switch_the_bus_once() {
+        let mut simulator = Simulator::default();
+        simulator.busy_us = 200;
+        let mut driver = simulator.driver(GEOMETRY);
+        driver.raw_data(b'A');
+        let switches = driver.bus_switches();
+        let mut polls = 0;
+        while driver.is_busy() == Ok(true) {
+            polls += 1;
+        }
+        assert!(polls > 1);
+        assert_eq!(driver.bus_switches(), switches + 1);
+
+        driver.raw_data(b'B');
+        assert_eq!(driver.bus_switches(), switches + 2);
+        let mut polls = 0;
+        while driver.busy_via_d7() == Some(true) {
+            polls += 1;
+        }
+        assert!(polls > 1);
+        assert_eq!(driver.bus_switches(), switches + 3);
+    }
+
+    #[test]
+    fn flush_coalesces_adjacent_writes() {
+        let mut driver = Simulator::default().driver(GEOMETRY);
+        for (address, value) in [(2, b'c'), (0x40, b'x'), (0, b'a'), (1, b'b'), (2, b'C')] {
+            driver.queue_write(address, value);
+        }
+        let (instructions, data_writes) = (driver.pins().instructions, driver.pins().data_writes);
+        driver.flush();
+        assert_eq!(driver.pins().instructions - instructions, 2);
+        assert_eq!(driver.pins().data_writes - data_writes, 4);
+        assert_eq!(driver.pins().first.ddram[..3], *b"abC");
+        assert_eq!(driver.pins().first.ddram[0x40], b'x');
+    }
+
+    #[test]
+    fn reads_unsupported_on_write_only_pins() {
+        let mut simulator = Simulator::default();
+        simulator.write_only = true;
+        let mut driver = simulator.driver(GEOMETRY);
+        assert!(!driver.capabilities().can_read);
+        assert_eq!(driver.read(), Err(Unsupported));
+        assert_eq!(driver.read_address_counter(), Err(Unsupported));
+        assert_eq!(driver.is_busy(), Err(Unsupported));
+        assert_eq!(driver.read_line_text(0, &mut [' '; 8]), Err(Unsupported));
+        assert_eq!(driver.bus_switches(), 0);
+    }
+
+    #[test]
+    fn busy_via_d7_on_write_only_pins() {
+        let mut simulator = Simulator::default();
+        simulator.write_only = true;
+        simulator.busy_us = 200;
+        let mut driver = simulator.driver(GEOMETRY);
+        driver.raw_data(b'A');
+        let mut polls = 0;
+        while driver.busy_via_d7() == Some(true) {
+            polls += 1;
+        }
+        assert!(polls > 1);
+        assert_eq!(driver.busy_via_d7(), Some(false));
+        assert_eq!(driver.bus_switches(), 0);
+    }
+
+    #[test]
+    fn capabilities_follow_the_pins() {
+        let mut driver = Simulator::default().driver(GEOMETRY);
+        assert_eq!(driver.capabilities(), Capabilities { can_read: true });
+        driver.raw_data(b'a');
+        driver.exec(cmd::Command::DdRamAddress(0));
+        assert_eq!(driver.read(), Ok(b'a'));
+
+        // The checks that need a read fail instead of trusting garbage
+        driver.pins().write_only = true;
+        assert_eq!(driver.capabilities(), Capabilities { can_read: false });
+        assert_eq!(driver.read(), Err(Unsupported));
+        assert!(!driver.probe());
+        assert!(!driver.resync());
+    }
+
+    #[test]
+    fn raw_instruction_and_data() {
+        let mut driver = Simulator::default().driver(GEOMETRY);
+        driver.raw_instruction(0b11000010);
+        assert_eq!(
+            (driver.pins().instructions, driver.pins().data_writes),
+            (1, 0)
+        );
+        driver.raw_data(b'Z');
+        assert_eq!(
+            (driver.pins().instructions, driver.pins().data_writes),
+            (1, 1)
+        );
+        assert_eq!(driver.pins().first.ddram[0x42], b'Z');
+    }
+
+    #[test]
+    fn upload_cgram() {
+        let heart = [0, 0b01010, 0b11111, 0b11111, 0b01110, 0b00100, 0, 0];
+        let stripes = [0b10101, 0, 0b10101, 0, 0b10101, 0, 0b10101, 0];
+        let cgram = CgRam::from_slice(&[heart, stripes]).unwrap();
+        let mut driver = Simulator::default().driver(GEOMETRY);
+        driver.upload_cgram(&cgram);
+        assert_eq!(driver.pins().first.cgram[..8], heart);
+        assert_eq!(driver.pins().first.cgram[8..16], stripes);
+        assert_eq!(driver.pins().data_writes, 16);
+        assert_eq!(driver.pins().instructions, 1);
+    }
+
+    #[test]
+    fn write_cgram_at() {
+        let mut driver = Simulator::default().driver(GEOMETRY);
+        driver.pins().first.cgram.fill(0b01010);
+        assert!(driver.write_cgram_at(2, 6, &[0b11111, 0b10001]));
+        let mut expected = [0b01010; 0x40];
+        expected[22..24].copy_from_slice(&[0b11111, 0b10001]);
+        assert_eq!(driver.pins().first.cgram, expected);
+
+        let data_writes = driver.pins().data_writes;
+        assert!(!driver.write_cgram_at(8, 0, &[0]));
+        assert!(!driver.write_cgram_at(2, 7, &[0, 0]));
+        assert_eq!(driver.pins().data_writes, data_writes);
+        assert_eq!(driver.pins().first.cgram, expected);
+    }
+
+    #[test]
+    fn sync_cgram_skips_the_matching_slots() {
+        let heart = [0, 0b01010, 0b11111, 0b11111, 0b01110, 0b00100, 0, 0];
+        let mut simulator = Simulator::default();
+        // NOTE: the bits above the fifth one are not part of the glyph
+        simulator.first.cgram[24..32].copy_from_slice(&heart.map(|v| v | 0b11100000));
+        let mut driver = simulator.driver(GEOMETRY);
+        let cgram = CgRam::from_slice(&[heart; 8]).unwrap();
+        let plan = driver.sync_cgram(&cgram);
+        assert!(!plan.contains(3));
+        assert_eq!(plan.slots().count(), 7);
+        driver.upload_cgram_plan(&cgram, plan);
+        assert_eq!(driver.pins().data_writes, 7 * 8);
+        let cgram = driver.pins().first.cgram.map(|v| v & 0b11111);
+        assert!(cgram.chunks(8).all(|glyph| glyph == heart));
+    }
+
+    #[test]
+    fn function_set_restores_the_state() {
+        use cmd::{Command::*, Direction::Left, Font::Size5x8, Lines::Two};
+
+        let mut simulator = Simulator::default();
+        simulator.first.function_set_resets = true;
+        let mut driver = simulator.driver(GEOMETRY);
+        driver.exec(Onoff {
+            display: true,
+            cursor: false,
+            blink: false,
+        });
+        driver.exec(EntryMode {
+            cursor: Left,
+            display: false,
+        });
+        driver.exec(FunctionSet {
+            lines: Two,
+            font: Size5x8,
+        });
+        assert!(driver.pins().first.display);
+
+        driver.exec(DdRamAddress(5));
+        driver.write(b'a');
+        driver.write(b'b');
+        assert_eq!(driver.pins().first.ddram[4..6], *b"ba");
+    }
+
+    #[test]
+    fn data_is_held_after_each_write() {
+        let mut driver = Simulator::default().driver(GEOMETRY);
+        // NOTE:
+        // Without the execution times the writes are back to back,
+        // so only the hold delay separates them
+        driver.set_timing(Timing {
+            write_hold_ns: 500,
+            data_us: 0,
+            ..driver.timing()
+        });
+        driver.pins().shortest_hold_ns = None;
+        b"abc".iter().for_each(|&v| driver.raw_data(v));
+        let hold = driver.pins().shortest_hold_ns.unwrap();
+        assert!((500..1000).contains(&hold));
+    }
+
+    #[test]
+    fn enable_pulse_waits_less_than_a_us() {
+        let driver = Simulator::default().driver(GEOMETRY);
+        let cycles = |ns| {
+            let start = get_cycle_count();
+            driver.delay_ns(ns);
+            get_cycle_count().wrapping_sub(start)
+        };
+        let pulse = cycles(cmd::ENABLE_PULSE_NS);
+        assert!(pulse >= cmd::ENABLE_PULSE_NS * driver.cpu_mhz / 1000);
+        assert!(pulse < driver.cpu_mhz);
+        assert!(cycles(cmd::READ_DELAY_NS) < driver.cpu_mhz);
+    }
+
+    #[test]
+    fn read_line_text() {
+        let mut driver = Simulator::default().driver(GEOMETRY);
+        driver.write_lines(&["", "Hello"]);
+        driver.pins().first.ddram[0x46] = 0;
+        let mut out = ['x'; 10];
+        assert_eq!(driver.read_line_text(1, &mut out), Ok(8));
+        assert_eq!(
+            out,
+            [
+                'H',
+                'e',
+                'l',
+                'l',
+                'o',
+                ' ',
+                char::REPLACEMENT_CHARACTER,
+                ' ',
+                'x',
+                'x'
+            ]
+        );
+        assert_eq!(driver.read_line_text(0, &mut out[..2]), Ok(2));
+        assert_eq!(out[..2], [' '; 2]);
     }
 }
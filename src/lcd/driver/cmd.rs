@@ -1,25 +1,25 @@
 /// Number of lines of the display
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Lines {
     One,
     Two,
 }
 
 /// Font size used by the display controller
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Font {
     Size5x11,
     Size5x8,
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Direction {
     Left,
     Right,
 }
 
 /// Shift direction and target
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Shift {
     /// Shift the entire display in the given [`Direction`]
     Display(Direction),
@@ -27,6 +27,37 @@ pub enum Shift {
     Cursor(Direction),
 }
 
+/// Execution time in µs of a data read or write, as indicated by the datasheet
+pub const WRITE_TIME_US: u32 = 37;
+
+/// Minimum time in µs the reset pin must be kept low
+pub const RESET_PULSE_US: u32 = 10;
+
+/// Minimum time in ns the enable pin must be kept high (PW_EH)
+pub const ENABLE_PULSE_NS: u32 = 460;
+
+/// Minimum time in ns of a whole enable cycle (t_C)
+pub const ENABLE_CYCLE_NS: u32 = 1200;
+
+/// Minimum time in ns the data must stay on the bus after the enable falling edge (t_H)
+pub const DATA_HOLD_NS: u32 = 10;
+
+/// Time in ns the data takes to be on the bus after the enable rising edge (t_DDR)
+pub const READ_DELAY_NS: u32 = 360;
+
+/// Bytes, and µs to wait after each one, of the handshake that resets
+/// the controller by instruction when using the 8-bit interface
+pub const EIGHT_BIT_HANDSHAKE: [(u8, u32); 3] =
+    [(0b00110000, 4100), (0b00110000, 100), (0b00110000, 40)];
+
+/// Nibbles, and µs to wait after each one, of the handshake that resets
+/// the controller by instruction and switches it to the 4-bit interface
+///
+/// The nibbles are meant to be sent on the `D7..D4` lines alone, since
+/// the controller is still expecting 8-bit transfers when they are sent
+pub const FOUR_BIT_HANDSHAKE: [(u8, u32); 4] =
+    [(0b0011, 4100), (0b0011, 100), (0b0011, 40), (0b0010, 40)];
+
 macro commands(
     $( #[ $enum_attr:meta ] )*
     $vis:vis enum $enum_name:ident {
@@ -45,7 +76,8 @@ macro commands(
         )*
     }
 
-    impl<Pins: super::Pins> Driver<Pins> {
+    #[cfg(feature = "methods")]
+    impl<Pins: super::Pins> Driver<'_, Pins> {
         $(
             display_command!{
                 $( #[doc = $doc ] )*
@@ -56,6 +88,7 @@ macro commands(
     }
 }
 
+#[cfg(feature = "methods")]
 macro display_command {
     (
         $( #[doc = $doc:literal ] )*
@@ -85,9 +118,10 @@ macro display_command {
     }
 }
 
+#[cfg(feature = "methods")]
 use super::Driver;
 commands! {
-    #[derive(Clone, Copy, Debug)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
     pub enum Command {
         /// Clear the display
         ///
@@ -147,6 +181,24 @@ commands! {
 }
 
 impl Command {
+    /// Whether the execution of the command takes a long time
+    ///
+    /// Only [`Clear`](Self::Clear) and [`ReturnHome`](Self::ReturnHome)
+    /// take milliseconds to complete, all the other commands are done
+    /// in tens of µs, so waiting for them is better done without yielding.
+    pub fn is_long(self) -> bool {
+        matches!(self, Self::Clear() | Self::ReturnHome())
+    }
+
+    /// Execution time in µs of the command, as indicated by the datasheet
+    pub fn exec_time_us(self) -> u32 {
+        if self.is_long() {
+            1600
+        } else {
+            40
+        }
+    }
+
     pub fn bits(self) -> u8 {
         use self::{Command::*, Direction::*, Font::*, Lines::*, Shift::*};
         match self {
@@ -260,3 +312,61 @@ impl Command {
 //         self.exec(Command::DdRamAddress(address))
 //     }
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_long() {
+        let long = [Command::Clear(), Command::ReturnHome()];
+        let short = [
+            Command::EntryMode {
+                cursor: Direction::Right,
+                display: false,
+            },
+            Command::Onoff {
+                display: true,
+                cursor: false,
+                blink: false,
+            },
+            Command::Shift(Shift::Cursor(Direction::Left)),
+            Command::FunctionSet {
+                lines: Lines::Two,
+                font: Font::Size5x8,
+            },
+            Command::CgRamAddress(0),
+            Command::DdRamAddress(0x40),
+        ];
+        assert!(long.into_iter().all(Command::is_long));
+        assert!(!short.into_iter().any(Command::is_long));
+        assert!(long.into_iter().all(|cmd| cmd.exec_time_us() > 1000));
+        assert!(short.into_iter().all(|cmd| cmd.exec_time_us() < 100));
+    }
+
+    /// Without the `methods` feature the calls resolve to the methods of
+    /// this trait, since the inherent ones are missing
+    #[cfg(not(feature = "methods"))]
+    #[test]
+    fn no_command_methods() {
+        use crate::lcd::driver::{Driver, Pins, Simulator};
+        use crate::lcd::Geometry;
+
+        trait Wrappers {
+            fn clear(&mut self) -> bool {
+                false
+            }
+        }
+        impl<P: Pins> Wrappers for Driver<'_, P> {}
+
+        let geometry = Geometry {
+            columns: 8,
+            lines: 2,
+            pixel_pitch_x: 6,
+            pixel_pitch_y: 9,
+        };
+        let mut driver = Simulator::default().driver(geometry);
+        assert!(!driver.clear());
+        assert_eq!(driver.pins().instructions, 0);
+    }
+}
@@ -0,0 +1,121 @@
+use super::Controller;
+
+/// Size of the **DDRAM** of a single controller
+const DDRAM_SIZE: u16 = 80;
+
+/// Width in pixels of a character
+const CHAR_WIDTH: u8 = 5;
+
+/// Height in pixels of a character
+const CHAR_HEIGHT: u8 = 8;
+
+/// Layout of the characters shown by the display
+///
+/// **Note** that this refers to how the controller sees the display, for
+/// example some 16x1 displays are actually driven as 8x2 ones
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Geometry {
+    /// Number of characters in each line
+    pub columns: u8,
+    /// Number of lines
+    pub lines: u8,
+    /// Distance in pixels between the left edges of two adjacent characters
+    ///
+    /// This includes the dead columns of the gap between them,
+    /// so it's `6` for a gap of one pixel and `5` for no gap
+    pub pixel_pitch_x: u8,
+    /// Distance in pixels between the top edges of two adjacent lines
+    ///
+    /// This includes the dead rows of the gap between them,
+    /// so it's `9` for a gap of one pixel and `8` for no gap
+    pub pixel_pitch_y: u8,
+}
+
+impl Geometry {
+    /// Returns the **DDRAM** address of the first character of the given line
+    ///
+    /// The first two lines start at `0x00` and `0x40`, while the third and
+    /// fourth (on 4 lines displays) continue right after the first two
+    ///
+    /// On displays driven by two controllers the third and fourth lines are the
+    /// first and second of the second controller (see [`controller_of`](Self::controller_of))
+    pub fn line_address(self, line: u8) -> u8 {
+        let line = if self.controllers() == 2 {
+            line % 2
+        } else {
+            line
+        };
+        let base = if line % 2 == 0 { 0x00 } else { 0x40 };
+        base + line / 2 * self.columns
+    }
+
+    /// Number of controllers needed to drive the display
+    ///
+    /// Displays with more characters than what fits in the **DDRAM**
+    /// of a single controller (e.g. 40x4) are driven by two of them,
+    /// sharing all the pins except the enable one
+    pub fn controllers(self) -> u8 {
+        if u16::from(self.columns) * u16::from(self.lines) > DDRAM_SIZE {
+            2
+        } else {
+            1
+        }
+    }
+
+    /// Maps a column of the pixels of the whole display to the character it falls in
+    ///
+    /// The pixels are counted from the left edge of the display, gaps included
+    /// (see [`pixel_pitch_x`](Self::pixel_pitch_x)), and the result is the
+    /// column of the character and the column of the pixel inside of it.
+    /// Returns [`None`] if the pixel falls in a gap or outside of the display.
+    pub fn pixel_column(self, x: u16) -> Option<(u8, u8)> {
+        Self::locate(x, self.pixel_pitch_x, CHAR_WIDTH, self.columns)
+    }
+
+    /// Maps a row of the pixels of the whole display to the line it falls in
+    ///
+    /// Same as [`pixel_column`](Self::pixel_column), but for the rows
+    /// (see [`pixel_pitch_y`](Self::pixel_pitch_y))
+    pub fn pixel_row(self, y: u16) -> Option<(u8, u8)> {
+        Self::locate(y, self.pixel_pitch_y, CHAR_HEIGHT, self.lines)
+    }
+
+    /// Splits a pixel coordinate into the index of the character and the offset inside of it
+    fn locate(px: u16, pitch: u8, size: u8, count: u8) -> Option<(u8, u8)> {
+        let pitch = u16::from(pitch.max(size));
+        let (index, offset) = (px / pitch, (px % pitch) as u8);
+        (index < u16::from(count) && offset < size).then_some((index as u8, offset))
+    }
+
+    /// Returns the controller that drives the given line
+    pub fn controller_of(self, line: u8) -> Controller {
+        if self.controllers() == 2 && line >= 2 {
+            Controller::Second
+        } else {
+            Controller::First
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pixel_with_a_one_pixel_gap() {
+        let geometry = Geometry {
+            columns: 8,
+            lines: 2,
+            pixel_pitch_x: 6,
+            pixel_pitch_y: 9,
+        };
+        assert_eq!(geometry.pixel_column(0), Some((0, 0)));
+        assert_eq!(geometry.pixel_column(4), Some((0, 4)));
+        assert_eq!(geometry.pixel_column(5), None);
+        assert_eq!(geometry.pixel_column(6), Some((1, 0)));
+        assert_eq!(geometry.pixel_column(20), Some((3, 2)));
+        assert_eq!(geometry.pixel_column(48), None);
+        assert_eq!(geometry.pixel_row(8), None);
+        assert_eq!(geometry.pixel_row(9), Some((1, 0)));
+    }
+}
@@ -0,0 +1,166 @@
+use super::{cmd, Driver, Geometry, Pins};
+use crate::lcd::canvas::CgRam;
+use crate::lcd::Bitmap;
+
+/// Two displays placed side by side and driven as a single wider one
+///
+/// Each display has its own [`Driver`], which usually shares all the pins
+/// with the other one except the enable pin. The columns of the `left`
+/// display come first, followed by the ones of the `right` display, and
+/// the operations are routed to the display of the column they refer to.
+///
+/// **Note** that each display has its own **CGRAM**, so the custom glyphs
+/// are allocated and uploaded separately for each display
+/// (see [`write_glyph_at`](Self::write_glyph_at))
+pub struct MultiDriver<'h, A: Pins, B: Pins> {
+    pub left: Driver<'h, A>,
+    pub right: Driver<'h, B>,
+    /// Glyphs uploaded to the **CGRAM** of the `left` and `right` displays
+    glyphs: [CgRam; 2],
+}
+
+impl<'h, A: Pins, B: Pins> MultiDriver<'h, A, B> {
+    /// Joins the two displays, which are expected to have the same number of lines
+    pub fn new(left: Driver<'h, A>, right: Driver<'h, B>) -> Self {
+        Self {
+            left,
+            right,
+            glyphs: Default::default(),
+        }
+    }
+
+    /// Returns the [`Geometry`] of the joined displays
+    ///
+    /// The columns are the ones of both displays, while the
+    /// lines and the pixel pitch are the ones of the `left` one
+    pub fn geometry(&self) -> Geometry {
+        let left = self.left.geometry();
+        Geometry {
+            columns: left.columns + self.right.geometry().columns,
+            ..left
+        }
+    }
+
+    /// Executes the given [`Command`](cmd::Command) on both displays
+    pub fn exec(&mut self, cmd: cmd::Command) {
+        self.left.exec(cmd);
+        self.right.exec(cmd);
+    }
+
+    /// Writes a byte in the **DDRAM** of the display that shows the given column
+    ///
+    /// The columns past the end of the `right` display are ignored
+    pub fn write_at(&mut self, line: u8, column: u8, value: u8) {
+        match self.locate(column) {
+            Some((0, column)) => Self::write_to(&mut self.left, line, column, value),
+            Some((_, column)) => Self::write_to(&mut self.right, line, column, value),
+            None => {}
+        }
+    }
+
+    /// Shows a custom glyph in the given column
+    ///
+    /// The glyph is allocated in the **CGRAM** of the display that shows the
+    /// column, reusing the slot of an identical glyph already uploaded there.
+    /// Returns `false`, writing nothing, if the column is past the end of the
+    /// `right` display or if all the slots of its display are taken.
+    pub fn write_glyph_at(&mut self, line: u8, column: u8, glyph: Bitmap) -> bool {
+        let located = self.locate(column);
+        let [left, right] = &mut self.glyphs;
+        match located {
+            Some((0, column)) => Self::glyph_to(&mut self.left, left, line, column, glyph),
+            Some((_, column)) => Self::glyph_to(&mut self.right, right, line, column, glyph),
+            None => false,
+        }
+    }
+
+    /// Forgets the glyphs allocated by [`write_glyph_at`](Self::write_glyph_at)
+    ///
+    /// The **CGRAM** is left untouched, but its slots will be overwritten
+    pub fn free_glyphs(&mut self) {
+        self.glyphs.iter_mut().for_each(CgRam::clear);
+    }
+
+    /// Returns the display that shows the given column, `0` for the `left`
+    /// one and `1` for the `right` one, and the column inside of it
+    fn locate(&self, column: u8) -> Option<(usize, u8)> {
+        let split = self.left.geometry().columns;
+        if column < split {
+            Some((0, column))
+        } else if column - split < self.right.geometry().columns {
+            Some((1, column - split))
+        } else {
+            None
+        }
+    }
+
+    /// Writes a byte in the **DDRAM** of the given display
+    fn write_to<P: Pins>(driver: &mut Driver<P>, line: u8, column: u8, value: u8) {
+        let address = driver.geometry().line_address(line) + column;
+        driver.exec(cmd::Command::DdRamAddress(address));
+        driver.write(value);
+    }
+
+    /// Shows a custom glyph on the given display, allocating it in its **CGRAM**
+    fn glyph_to<P: Pins>(
+        driver: &mut Driver<P>,
+        glyphs: &mut CgRam,
+        line: u8,
+        column: u8,
+        glyph: Bitmap,
+    ) -> bool {
+        let raw = glyph.raw();
+        let slot = match glyphs.iter().position(|uploaded| *uploaded == raw) {
+            Some(slot) => slot as u8,
+            None => {
+                if glyphs.push(raw).is_err() {
+                    return false;
+                }
+                let slot = glyphs.len() as u8 - 1;
+                driver.exec(cmd::Command::CgRamAddress(slot * 8));
+                raw.into_iter().for_each(|v| driver.write(v));
+                slot
+            }
+        };
+        Self::write_to(driver, line, column, slot);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lcd::driver::Simulator;
+
+    /// Geometry of each of the displays of the tests, a 16x2 module
+    const GEOMETRY: Geometry = Geometry {
+        columns: 16,
+        lines: 2,
+        pixel_pitch_x: 6,
+        pixel_pitch_y: 9,
+    };
+
+    #[test]
+    fn write_at_the_second_display() {
+        let left = Simulator::default().driver(GEOMETRY);
+        let right = Simulator::default().driver(GEOMETRY);
+        let mut multi = MultiDriver::new(left, right);
+        assert_eq!(multi.geometry().columns, 32);
+
+        multi.write_at(0, 20, b'X');
+        assert_eq!(multi.right.pins().first.ddram[4], b'X');
+        assert_eq!(multi.left.pins().first.ddram[4], b' ');
+
+        let heart = Bitmap::new([0, 0b01010, 0b11111, 0b11111, 0b01110, 0b00100, 0, 0]);
+        assert!(multi.write_glyph_at(1, 20, heart));
+        assert_eq!(multi.right.pins().first.cgram_bitmap(0), heart);
+        assert_eq!(multi.right.pins().first.ddram[0x44], 0);
+        assert_eq!(multi.left.pins().first.cgram_bitmap(0), Bitmap::default());
+
+        // Each display allocates its own slots
+        let block = Bitmap::new([0b10101; 8]);
+        assert!(multi.write_glyph_at(0, 1, block));
+        assert_eq!(multi.left.pins().first.cgram_bitmap(0), block);
+        assert_eq!(multi.right.pins().first.cgram_bitmap(0), heart);
+    }
+}
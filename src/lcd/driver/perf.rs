@@ -0,0 +1,79 @@
+use heapless::HistoryBuffer;
+
+/// Number of the last durations averaged by [`OpStats::average_us`]
+const AVERAGE_WINDOW: usize = 16;
+
+/// Durations measured for a kind of operation of the [`Driver`](super::Driver)
+///
+/// The durations are measured in CPU cycles and converted to µs (rounded up),
+/// and include the time waited for the operation to complete
+#[derive(Default)]
+pub struct OpStats {
+    min: Option<u32>,
+    max: u32,
+    last: HistoryBuffer<u32, AVERAGE_WINDOW>,
+}
+
+impl OpStats {
+    /// Records the duration of an operation
+    pub fn record(&mut self, us: u32) {
+        self.min = Some(self.min.map_or(us, |min| min.min(us)));
+        self.max = self.max.max(us);
+        self.last.write(us);
+    }
+
+    /// Shortest duration recorded, [`None`] if nothing was recorded
+    pub fn min_us(&self) -> Option<u32> {
+        self.min
+    }
+
+    /// Longest duration recorded, `0` if nothing was recorded
+    pub fn max_us(&self) -> u32 {
+        self.max
+    }
+
+    /// Average of the last durations recorded, [`None`] if nothing was recorded
+    pub fn average_us(&self) -> Option<u32> {
+        let len = self.last.len() as u32;
+        (len != 0).then(|| self.last.iter().sum::<u32>() / len)
+    }
+}
+
+/// Durations measured for each kind of operation of the [`Driver`](super::Driver)
+///
+/// The kinds are the same of the [`Timing`](super::Timing)
+#[derive(Default)]
+pub struct PerfStats {
+    /// [`Clear`](super::cmd::Command::Clear) and
+    /// [`ReturnHome`](super::cmd::Command::ReturnHome) commands
+    pub long: OpStats,
+    /// All the other commands
+    pub short: OpStats,
+    /// Data writes
+    pub write: OpStats,
+    /// Data reads
+    pub read: OpStats,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn op_stats() {
+        let mut stats = OpStats::default();
+        assert_eq!(stats.min_us(), None);
+        assert_eq!(stats.max_us(), 0);
+        assert_eq!(stats.average_us(), None);
+
+        [37, 41, 39, 43].into_iter().for_each(|us| stats.record(us));
+        assert_eq!(stats.min_us(), Some(37));
+        assert_eq!(stats.max_us(), 43);
+        assert_eq!(stats.average_us(), Some(40));
+
+        // Only the last durations are averaged, while the extremes are kept
+        (0..AVERAGE_WINDOW).for_each(|_| stats.record(1520));
+        assert_eq!(stats.min_us(), Some(37));
+        assert_eq!(stats.average_us(), Some(1520));
+    }
+}
@@ -0,0 +1,351 @@
+use hal::xtensa_lx::timer::get_cycle_count;
+use hal::{clock::ClockControl, peripherals::Peripherals, prelude::*};
+
+use super::{Driver, Geometry, Pins};
+use crate::lcd::Bitmap;
+
+/// A display simulated at the level of its pins, for the tests
+///
+/// On the falling edge of an enable pin the value on the bus is executed
+/// by the controller it drives, like the **ST7066U** does: the address
+/// commands move the **AC**, and the data is written to the **DDRAM** or to
+/// the **CGRAM**, moving the **AC** as set by the last
+/// [`EntryMode`](super::cmd::Command::EntryMode), while
+/// [`Onoff`](super::cmd::Command::Onoff) turns the display on or off
+/// and [`FunctionSet`](super::cmd::Command::FunctionSet) selects the
+/// interface. The other commands are ignored.
+///
+/// With the 4-bit interface only the `D7..D4` lines are used, and each
+/// byte takes two enable pulses, the most significant nibble first.
+///
+/// Each write is executed right away, but the busy flag is then reported
+/// for [`busy_us`](Self::busy_us), measured with the CPU cycle counter.
+/// Releasing the reset pin brings both controllers back to the state
+/// they have when powered up.
+pub struct Simulator {
+    /// Controller driven by the first enable pin
+    pub first: Chip,
+    /// Controller driven by the second enable pin, only used by the displays
+    /// driven by two controllers (see [`Geometry::controllers`])
+    pub second: Chip,
+    /// Value returned by every read, like a bus left floating
+    pub stuck: Option<u8>,
+    /// Whether the data pins can only be written (see [`Pins::can_read`])
+    pub write_only: bool,
+    /// Time in µs the busy flag stays set after each write, `0` by default
+    pub busy_us: u32,
+    /// Number of bytes written to the _Instruction Register_
+    pub instructions: u32,
+    /// Number of bytes written to the _Data Register_
+    pub data_writes: u32,
+    /// Whether the backlight is on
+    pub backlight: bool,
+    /// Length in µs of the last pulse on the reset pin
+    pub reset_us: Option<u32>,
+    /// Shortest time in ns the bus was left untouched after the falling
+    /// edge of an enable pin that ended a write
+    pub shortest_hold_ns: Option<u32>,
+    /// Value of the CPU cycle counter at the falling edge that ended the last write
+    written_at: Option<u32>,
+    /// Value of the CPU cycle counter when the reset pin was asserted
+    reset_since: Option<u32>,
+    cpu_mhz: u32,
+    rs: bool,
+    rw: bool,
+    en: bool,
+    en2: bool,
+    bus: u8,
+}
+
+/// State of one of the controllers of the [`Simulator`]
+pub struct Chip {
+    /// Contents of the **DDRAM**, by address
+    pub ddram: [u8; 0x80],
+    /// Contents of the **CGRAM**, 8 rows for each slot
+    pub cgram: [u8; 0x40],
+    /// Whether the display is on, it's off when powered up
+    pub display: bool,
+    /// Whether the 4-bit interface is in use, it's the 8-bit one when powered up
+    pub four_bit: bool,
+    /// Whether a [`FunctionSet`](super::cmd::Command::FunctionSet) turns off
+    /// the display and resets the entry mode, like some controllers do
+    pub function_set_resets: bool,
+    /// Most significant nibble of the byte being written with the 4-bit interface
+    nibble: Option<u8>,
+    /// Whether the least significant nibble is the next one read
+    /// with the 4-bit interface
+    low_half: bool,
+    /// Value of the CPU cycle counter at the last write
+    busy_since: Option<u32>,
+    ac: u8,
+    in_cgram: bool,
+    increment: bool,
+}
+
+impl Default for Simulator {
+    fn default() -> Self {
+        Self {
+            first: Chip::default(),
+            second: Chip::default(),
+            stuck: None,
+            write_only: false,
+            busy_us: 0,
+            instructions: 0,
+            data_writes: 0,
+            backlight: true,
+            reset_us: None,
+            shortest_hold_ns: None,
+            written_at: None,
+            reset_since: None,
+            cpu_mhz: 1,
+            rs: false,
+            rw: false,
+            en: false,
+            en2: false,
+            bus: 0,
+        }
+    }
+}
+
+impl Default for Chip {
+    fn default() -> Self {
+        Self {
+            ddram: [b' '; 0x80],
+            cgram: [0; 0x40],
+            display: false,
+            four_bit: false,
+            function_set_resets: false,
+            nibble: None,
+            low_half: false,
+            busy_since: None,
+            ac: 0,
+            in_cgram: false,
+            increment: true,
+        }
+    }
+}
+
+impl Simulator {
+    /// Sets up a [`Driver`] for the simulated display
+    pub fn driver<'h>(mut self, geometry: Geometry) -> Driver<'h, Self> {
+        // SAFETY:
+        // The clocks are only used to compute the delays
+        let peripherals = unsafe { Peripherals::steal() };
+        let system = peripherals.DPORT.split();
+        let clocks = ClockControl::boot_defaults(system.clock_control).freeze();
+        self.cpu_mhz = clocks.cpu_clock.to_MHz();
+        Driver::setup(self, geometry, &clocks)
+    }
+
+    /// Executes the operation on the bus on the falling edge of an enable pin
+    fn falling_edge(&mut self, second: bool) {
+        let (rs, rw, bus) = (self.rs, self.rw, self.bus);
+        let chip = if second {
+            &mut self.second
+        } else {
+            &mut self.first
+        };
+        let done = chip.execute(rs, rw, bus);
+        // NOTE:
+        // When both controllers are enabled the write is counted once,
+        // at the end of the enable pulse
+        if !rw && !self.en && !self.en2 {
+            self.written_at = Some(get_cycle_count());
+        }
+        if done && !rw && !self.en && !self.en2 {
+            if rs {
+                self.data_writes += 1;
+            } else {
+                self.instructions += 1;
+            }
+        }
+    }
+}
+
+impl Chip {
+    /// Returns the glyph currently in the given slot of the **CGRAM**
+    ///
+    /// Only the 3 least significant bits of `slot` are used
+    pub fn cgram_bitmap(&self, slot: u8) -> Bitmap {
+        let start = usize::from(slot & 7) * 8;
+        Bitmap::new(self.cgram[start..start + 8].try_into().unwrap())
+    }
+
+    /// Executes a read or a write of a register
+    ///
+    /// Returns `false` if it was only the first nibble of a byte
+    fn execute(&mut self, rs: bool, rw: bool, bus: u8) -> bool {
+        let bus = match (self.four_bit, rw) {
+            (false, _) => bus,
+            (true, false) => match self.nibble.take() {
+                Some(high) => high | bus >> 4,
+                None => {
+                    self.nibble = Some(bus & 0xf0);
+                    return false;
+                }
+            },
+            (true, true) => {
+                self.low_half = !self.low_half;
+                if self.low_half {
+                    return false;
+                }
+                bus
+            }
+        };
+        if !rw {
+            self.busy_since = Some(get_cycle_count());
+        }
+        match (rs, rw) {
+            (false, false) => self.instruction(bus),
+            (true, false) => {
+                let ac = usize::from(self.ac);
+                self.memory()[ac] = bus;
+                self.advance();
+            }
+            (true, true) => self.advance(),
+            (false, true) => {}
+        }
+        true
+    }
+
+    /// Executes a byte written to the _Instruction Register_
+    fn instruction(&mut self, value: u8) {
+        // NOTE:
+        // Each instruction is identified by its most significant bit set
+        match value.leading_zeros() {
+            0 => (self.ac, self.in_cgram) = (value & 0x7f, false),
+            1 => (self.ac, self.in_cgram) = (value & 0x3f, true),
+            2 => {
+                self.four_bit = value & 0x10 == 0;
+                if self.function_set_resets {
+                    (self.display, self.increment) = (false, true);
+                }
+            }
+            4 => self.display = value & 0x04 != 0,
+            5 => self.increment = value & 0x02 != 0,
+            6 => (self.ac, self.in_cgram) = (0, false),
+            7 => {
+                self.ddram.fill(b' ');
+                (self.ac, self.in_cgram, self.increment) = (0, false, true);
+            }
+            _ => {}
+        }
+    }
+
+    /// Whether the busy flag is set, `busy_cycles` after the last write
+    fn busy(&self, busy_cycles: u32) -> bool {
+        let Some(since) = self.busy_since else {
+            return false;
+        };
+        get_cycle_count().wrapping_sub(since) < busy_cycles
+    }
+
+    /// Reads the register selected by `rs`
+    fn read(&mut self, rs: bool, busy_cycles: u32) -> u8 {
+        let value = if rs {
+            let ac = usize::from(self.ac);
+            self.memory()[ac]
+        } else {
+            self.ac | u8::from(self.busy(busy_cycles)) << 7
+        };
+        match (self.four_bit, self.low_half) {
+            (false, _) => value,
+            (true, false) => value & 0xf0,
+            (true, true) => value << 4,
+        }
+    }
+
+    /// Returns the memory the **AC** points to
+    fn memory(&mut self) -> &mut [u8] {
+        if self.in_cgram {
+            &mut self.cgram
+        } else {
+            &mut self.ddram
+        }
+    }
+
+    /// Moves the **AC** after a data read or write
+    fn advance(&mut self) {
+        let len = self.memory().len() as u8;
+        self.ac = if self.increment {
+            (self.ac + 1) % len
+        } else {
+            (self.ac + len - 1) % len
+        };
+    }
+}
+
+impl Pins for Simulator {
+    fn set_rs(&mut self, value: bool) {
+        self.rs = value;
+    }
+
+    fn set_rw(&mut self, value: bool) {
+        self.rw = value;
+    }
+
+    fn set_en(&mut self, value: bool) {
+        let falling = self.en && !value;
+        self.en = value;
+        if falling {
+            self.falling_edge(false);
+        }
+    }
+
+    fn set_en2(&mut self, value: bool) {
+        let falling = self.en2 && !value;
+        self.en2 = value;
+        if falling {
+            self.falling_edge(true);
+        }
+    }
+
+    fn write(&mut self, value: u8) {
+        if let Some(since) = self.written_at.take() {
+            let cycles = u64::from(get_cycle_count().wrapping_sub(since));
+            let held = u32::try_from(cycles * 1000 / u64::from(self.cpu_mhz)).unwrap_or(u32::MAX);
+            self.shortest_hold_ns = Some(self.shortest_hold_ns.map_or(held, |ns| ns.min(held)));
+        }
+        self.bus = value;
+    }
+
+    fn read(&mut self) -> u8 {
+        if let Some(value) = self.stuck {
+            return value;
+        }
+        let busy_cycles = self.busy_us * self.cpu_mhz;
+        // NOTE:
+        // The reads are only done from one controller at a time
+        if self.en2 && !self.en {
+            self.second.read(self.rs, busy_cycles)
+        } else {
+            self.first.read(self.rs, busy_cycles)
+        }
+    }
+
+    fn can_read(&self) -> bool {
+        !self.write_only
+    }
+
+    fn read_d7(&mut self) -> Option<bool> {
+        Some(self.read() & 0b10000000 != 0)
+    }
+
+    fn set_backlight(&mut self, on: bool) {
+        self.backlight = on;
+    }
+
+    fn set_reset(&mut self, active: bool) {
+        if active {
+            self.reset_since.get_or_insert_with(get_cycle_count);
+        } else if let Some(since) = self.reset_since.take() {
+            self.reset_us = Some(get_cycle_count().wrapping_sub(since) / self.cpu_mhz);
+            for chip in [&mut self.first, &mut self.second] {
+                *chip = Chip {
+                    function_set_resets: chip.function_set_resets,
+                    ..Chip::default()
+                };
+            }
+        }
+    }
+}
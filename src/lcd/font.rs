@@ -0,0 +1,194 @@
+//! Glyphs of the Latin-1 characters missing from the built-in font
+//!
+//! The accented letters are composed by placing the accent over the glyph
+//! of their base letter, while the others are drawn one by one.
+//! None of these is in the **DDROM**, so each one takes a **CGRAM** slot.
+
+use super::Bitmap;
+
+/// Diacritic placed over (or under) a base letter
+#[derive(Clone, Copy)]
+enum Accent {
+    Grave,
+    Acute,
+    Circumflex,
+    Tilde,
+    Diaeresis,
+    Ring,
+    Cedilla,
+}
+
+impl Accent {
+    /// Rows of the accent over a lowercase letter, which leaves two free rows
+    fn lower(self) -> [u8; 2] {
+        match self {
+            Self::Grave => [0b01000, 0b00100],
+            Self::Acute => [0b00010, 0b00100],
+            Self::Circumflex => [0b00100, 0b01010],
+            Self::Tilde => [0b01101, 0b10010],
+            Self::Diaeresis => [0b01010, 0b00000],
+            Self::Ring => [0b01110, 0b01010],
+            Self::Cedilla => [0b00000, 0b00000],
+        }
+    }
+
+    /// Row of the accent over an uppercase letter, which leaves a single free row
+    fn upper(self) -> u8 {
+        match self {
+            Self::Grave => 0b01000,
+            Self::Acute => 0b00010,
+            Self::Circumflex | Self::Ring => 0b00100,
+            Self::Tilde => 0b01110,
+            Self::Diaeresis => 0b01010,
+            Self::Cedilla => 0b00000,
+        }
+    }
+}
+
+/// Returns the glyph of a Latin-1 character that is not in the built-in font
+pub fn rasterize(ch: char) -> Option<Bitmap> {
+    if let Some(&glyph) = GLYPHS.get(&ch) {
+        return Some(Bitmap::new(glyph));
+    }
+    let (base, accent) = decompose(ch)?;
+    let mut rows = Bitmap::render(base).raw();
+    match accent {
+        // NOTE:
+        // The cedilla goes in the last row, which is always free
+        Accent::Cedilla => rows[7] = 0b00100,
+        // NOTE:
+        // The second row of the uppercase letters is dropped to make room for
+        // the accent, as it's almost always the same as the third one
+        _ if base.is_ascii_uppercase() => {
+            rows[1] = rows[0];
+            rows[0] = accent.upper();
+        }
+        // NOTE:
+        // The lowercase letters have two free rows on top, except for the dot of the `i`
+        _ => rows[..2].copy_from_slice(&accent.lower()),
+    }
+    Some(Bitmap::new(rows))
+}
+
+/// Splits an accented letter into its base letter and its accent
+fn decompose(ch: char) -> Option<(char, Accent)> {
+    use Accent::*;
+    let decomposed = match ch {
+        'À' => ('A', Grave),
+        'Á' => ('A', Acute),
+        'Â' => ('A', Circumflex),
+        'Ã' => ('A', Tilde),
+        'Ä' => ('A', Diaeresis),
+        'Å' => ('A', Ring),
+        'Ç' => ('C', Cedilla),
+        'È' => ('E', Grave),
+        'É' => ('E', Acute),
+        'Ê' => ('E', Circumflex),
+        'Ë' => ('E', Diaeresis),
+        'Ì' => ('I', Grave),
+        'Í' => ('I', Acute),
+        'Î' => ('I', Circumflex),
+        'Ï' => ('I', Diaeresis),
+        'Ñ' => ('N', Tilde),
+        'Ò' => ('O', Grave),
+        'Ó' => ('O', Acute),
+        'Ô' => ('O', Circumflex),
+        'Õ' => ('O', Tilde),
+        'Ö' => ('O', Diaeresis),
+        'Ù' => ('U', Grave),
+        'Ú' => ('U', Acute),
+        'Û' => ('U', Circumflex),
+        'Ü' => ('U', Diaeresis),
+        'Ý' => ('Y', Acute),
+        'à' => ('a', Grave),
+        'á' => ('a', Acute),
+        'â' => ('a', Circumflex),
+        'ã' => ('a', Tilde),
+        'ä' => ('a', Diaeresis),
+        'å' => ('a', Ring),
+        'ç' => ('c', Cedilla),
+        'è' => ('e', Grave),
+        'é' => ('e', Acute),
+        'ê' => ('e', Circumflex),
+        'ë' => ('e', Diaeresis),
+        'ì' => ('i', Grave),
+        'í' => ('i', Acute),
+        'î' => ('i', Circumflex),
+        'ï' => ('i', Diaeresis),
+        'ñ' => ('n', Tilde),
+        'ò' => ('o', Grave),
+        'ó' => ('o', Acute),
+        'ô' => ('o', Circumflex),
+        'õ' => ('o', Tilde),
+        'ö' => ('o', Diaeresis),
+        'ù' => ('u', Grave),
+        'ú' => ('u', Acute),
+        'û' => ('u', Circumflex),
+        'ü' => ('u', Diaeresis),
+        'ý' => ('y', Acute),
+        'ÿ' => ('y', Diaeresis),
+        _ => return None,
+    };
+    Some(decomposed)
+}
+
+/// Glyphs of the characters that are not made of a base letter and an accent
+static GLYPHS: phf::Map<char, [u8; 8]> = phf::phf_map! {
+    '\\' => [0b00000, 0b10000, 0b01000, 0b00100, 0b00010, 0b00001, 0b00000, 0b00000],
+    '~' => [0b00000, 0b00000, 0b01000, 0b10101, 0b00010, 0b00000, 0b00000, 0b00000],
+    '\u{a0}' => [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000],
+    '¡' => [0b00100, 0b00000, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00000],
+    '¢' => [0b00100, 0b01110, 0b10100, 0b10100, 0b10101, 0b01110, 0b00100, 0b00000],
+    '£' => [0b00110, 0b01001, 0b01000, 0b11100, 0b01000, 0b01001, 0b10110, 0b00000],
+    '¤' => [0b00000, 0b10001, 0b01110, 0b01010, 0b01110, 0b10001, 0b00000, 0b00000],
+    '¦' => [0b00100, 0b00100, 0b00100, 0b00000, 0b00100, 0b00100, 0b00100, 0b00000],
+    '§' => [0b01110, 0b10000, 0b01110, 0b10001, 0b01110, 0b00001, 0b01110, 0b00000],
+    '¨' => [0b01010, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000],
+    '©' => [0b01110, 0b10001, 0b10111, 0b10101, 0b10111, 0b10001, 0b01110, 0b00000],
+    'ª' => [0b01110, 0b00001, 0b01111, 0b10001, 0b01111, 0b00000, 0b11111, 0b00000],
+    '«' => [0b00000, 0b00101, 0b01010, 0b10100, 0b01010, 0b00101, 0b00000, 0b00000],
+    '¬' => [0b00000, 0b00000, 0b11111, 0b00001, 0b00001, 0b00000, 0b00000, 0b00000],
+    '\u{ad}' => [0b00000, 0b00000, 0b00000, 0b11111, 0b00000, 0b00000, 0b00000, 0b00000],
+    '®' => [0b01110, 0b11101, 0b11011, 0b11101, 0b11011, 0b10001, 0b01110, 0b00000],
+    '¯' => [0b11111, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000],
+    '°' => [0b01100, 0b10010, 0b10010, 0b01100, 0b00000, 0b00000, 0b00000, 0b00000],
+    '±' => [0b00100, 0b00100, 0b11111, 0b00100, 0b00100, 0b00000, 0b11111, 0b00000],
+    '²' => [0b01100, 0b10010, 0b00100, 0b01000, 0b11110, 0b00000, 0b00000, 0b00000],
+    '³' => [0b11100, 0b00010, 0b01100, 0b00010, 0b11100, 0b00000, 0b00000, 0b00000],
+    '´' => [0b00010, 0b00100, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000],
+    'µ' => [0b00000, 0b00000, 0b10001, 0b10001, 0b10011, 0b11101, 0b10000, 0b10000],
+    '¶' => [0b01111, 0b11101, 0b11101, 0b01101, 0b00101, 0b00101, 0b00101, 0b00000],
+    '·' => [0b00000, 0b00000, 0b00000, 0b00100, 0b00000, 0b00000, 0b00000, 0b00000],
+    '¸' => [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00100, 0b01000],
+    '¹' => [0b00100, 0b01100, 0b00100, 0b00100, 0b01110, 0b00000, 0b00000, 0b00000],
+    'º' => [0b01110, 0b10001, 0b10001, 0b01110, 0b00000, 0b11111, 0b00000, 0b00000],
+    '»' => [0b00000, 0b10100, 0b01010, 0b00101, 0b01010, 0b10100, 0b00000, 0b00000],
+    '¼' => [0b10000, 0b10010, 0b10100, 0b01010, 0b10110, 0b01111, 0b00010, 0b00000],
+    '½' => [0b10000, 0b10010, 0b10100, 0b01011, 0b10001, 0b00010, 0b00111, 0b00000],
+    '¾' => [0b11000, 0b01010, 0b11100, 0b01010, 0b10110, 0b01111, 0b00010, 0b00000],
+    '¿' => [0b00100, 0b00000, 0b00100, 0b01000, 0b10000, 0b10001, 0b01110, 0b00000],
+    'Æ' => [0b01111, 0b10100, 0b10100, 0b11111, 0b10100, 0b10100, 0b10111, 0b00000],
+    'Ð' => [0b11100, 0b10010, 0b10001, 0b11101, 0b10001, 0b10010, 0b11100, 0b00000],
+    '×' => [0b00000, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b00000, 0b00000],
+    'Ø' => [0b01111, 0b10011, 0b10101, 0b10101, 0b10101, 0b11001, 0b11110, 0b00000],
+    'Þ' => [0b10000, 0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b00000],
+    'ß' => [0b01100, 0b10010, 0b10010, 0b10110, 0b10001, 0b10001, 0b10110, 0b00000],
+    'æ' => [0b00000, 0b00000, 0b11010, 0b00101, 0b01111, 0b10100, 0b01111, 0b00000],
+    'ð' => [0b01010, 0b00100, 0b01010, 0b00001, 0b01111, 0b10001, 0b01110, 0b00000],
+    '÷' => [0b00000, 0b00100, 0b00000, 0b11111, 0b00000, 0b00100, 0b00000, 0b00000],
+    'ø' => [0b00000, 0b00000, 0b01111, 0b10011, 0b10101, 0b11001, 0b11110, 0b00000],
+    'þ' => [0b00000, 0b10000, 0b11110, 0b10001, 0b11110, 0b10000, 0b10000, 0b00000],
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rasterize_c_cedilla() {
+        let expected = [0, 0, 0b01110, 0b10000, 0b10000, 0b10001, 0b01110, 0b00100];
+        assert_eq!(rasterize('ç'), Some(Bitmap::new(expected)));
+        assert_eq!(Bitmap::render('ç'), Bitmap::new(expected));
+        assert_eq!(rasterize('c'), None);
+    }
+}
@@ -12,13 +12,14 @@ use esp_backtrace as _;
 
 use hal::gpio::*;
 use hal::prelude::*;
-use hal::{clock::ClockControl, peripherals::Peripherals, timer::TimerGroup, Delay, Rtc, IO};
+use hal::{clock::ClockControl, peripherals::Peripherals, timer::TimerGroup, Rtc, IO};
 
-use core::iter::zip;
+use core::array::from_fn;
 
 mod lcd;
-use lcd::canvas::{Canvas, DdRam};
-use lcd::cmd::{Direction::Right, Font::Size5x8, Lines::Two};
+use lcd::canvas::{ddram_address, Canvas, CgRam};
+use lcd::cmd::{Command, Direction::Right, Font::Size5x8, Lines::Two};
+use lcd::Geometry;
 
 #[macro_use]
 mod bus;
@@ -65,15 +66,6 @@ impl lcd::Pins for Pins {
 
 mod timer;
 
-fn changes<T: PartialEq>(
-    old: impl IntoIterator<Item = T>,
-    new: impl IntoIterator<Item = T>,
-) -> impl Iterator<Item = (usize, T)> {
-    zip(old, new)
-        .enumerate()
-        .filter_map(|(i, (old, new))| (old != new).then_some((i, new)))
-}
-
 #[entry]
 fn main() -> ! {
     let peripherals = Peripherals::take();
@@ -117,39 +109,61 @@ fn main() -> ! {
                 d7: pins.gpio32.into(),
             },
         },
+        Geometry {
+            columns: 8,
+            lines: 2,
+            pixel_pitch_x: 6,
+            pixel_pitch_y: 9,
+        },
         &clocks,
     );
 
-    display.function_set(Two, Size5x8);
-    display.clear();
-    display.onoff(true, false, false);
-    display.entry_mode_set(Right, false);
+    display.exec(Command::FunctionSet {
+        lines: Two,
+        font: Size5x8,
+    });
+    display.exec(Command::Clear());
+    display.exec(Command::Onoff {
+        display: true,
+        cursor: false,
+        blink: false,
+    });
+    display.exec(Command::EntryMode {
+        cursor: Right,
+        display: false,
+    });
 
     let mut canvas = Canvas::default();
     canvas.write("Hello World!", None);
     canvas.approx = true;
 
-    let mut old_ddram = DdRam::default();
-    display.set_ddram_address(0);
-    old_ddram.iter_mut().for_each(|v| *v = display.read());
+    // NOTE:
+    // The contents left on the display by a previous run are read back,
+    // so that only what differs from them is written
+    let old_ddram = from_fn(|i| {
+        display.exec(Command::DdRamAddress(ddram_address(i)));
+        display.read().unwrap()
+    });
+
+    let mut old_cgram = CgRam::new();
+    display.exec(Command::CgRamAddress(0));
+    for _ in 0..old_cgram.capacity() {
+        let glyph = from_fn(|_| display.read().unwrap());
+        old_cgram.push(glyph).unwrap();
+    }
 
-    let mut old_cgram = [[0u8; 8]; 8];
-    display.set_cgram_address(0);
-    old_cgram
-        .flatten_mut()
-        .iter_mut()
-        .for_each(|v| *v = display.read());
+    let mut old = (old_ddram, old_cgram);
 
     const REPRINT_HEADERS_CYCLES: usize = 20;
     let mut cycle = 0usize;
 
     let mut timebuf = heapless::HistoryBuffer::<_, 100>::new();
-    let mut report = move |total, render, cgram, ddram| {
+    let mut report = move |total, render, update| {
         if cycle == 0 {
             esp_println::print!(concat!(
-                "+------------+------------+------------+------------+------------+\n",
-                "|  Average   |   Total    | Rendering  |   CGRAM    |   DDRAM    |\n",
-                "+------------+------------+------------+------------+------------+\n",
+                "+------------+------------+------------+------------+\n",
+                "|  Average   |   Total    | Rendering  |  Updating  |\n",
+                "+------------+------------+------------+------------+\n",
             ));
         }
         cycle += 1;
@@ -163,53 +177,35 @@ fn main() -> ! {
         let average = average as f32 / 1000f32;
         let total = total as f32 / 1000f32;
         let render = render as f32 / 1000f32;
-        let cgram = cgram as f32 / 1000f32;
-        let ddram = ddram as f32 / 1000f32;
+        let update = update as f32 / 1000f32;
 
         esp_println::println!(
-            "| {average:>8.3}ms | {total:>8.3}ms | {render:>8.3}ms | {cgram:>8.3}ms | {ddram:>8.3}ms |"
+            "| {average:>8.3}ms | {total:>8.3}ms | {render:>8.3}ms | {update:>8.3}ms |"
         );
     };
 
+    let mut scroll = timer::Scroll::new(5);
+
     loop {
-        timer::reset();
+        let px = scroll.advance(timer::take_elapsed_us());
+        if px == 0 {
+            continue;
+        }
+        (0..px).for_each(|_| canvas.shift_left(None));
 
-        let (ddram, cgram) = canvas.render();
+        let frame = canvas.render();
 
         let elapsed_render = timer::elapsed_us();
 
-        changes(old_cgram.flatten(), cgram.flatten()).fold(cgram.len(), |at, (i, v)| {
-            if at != i {
-                display.set_cgram_address(i as u8);
-            }
-            display.write(*v);
-            i + 1
-        });
-        old_cgram[..cgram.len()].copy_from_slice(&cgram);
-
-        let elapsed_cgram = timer::elapsed_us();
-
-        changes(old_ddram, ddram)
-            .map(|(i, v)| (if i >= 8 { i - 8 + 0x40 } else { i }, v))
-            .fold(ddram.len(), |at, (i, v)| {
-                if at != i {
-                    display.set_ddram_address(i as u8);
-                }
-                display.write(v);
-                i + 1
-            });
-        old_ddram = ddram;
-
-        let elapsed_ddram = timer::elapsed_us();
+        display.present_frame(Some(&old), &frame);
+        old = frame;
+
+        let elapsed_update = timer::elapsed_us();
 
         report(
             timer::elapsed_us(),
             elapsed_render,
-            elapsed_cgram - elapsed_render,
-            elapsed_ddram - elapsed_cgram,
+            elapsed_update - elapsed_render,
         );
-
-        canvas.shift_left(None);
-        Delay::new(&clocks).delay(200_000);
     }
 }
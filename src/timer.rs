@@ -10,8 +10,9 @@
 //! Before using the timer the [`init()`] function *should* be
 //! called.
 //! 
-//! The [`elapsed_us()`] and [`reset()`] functions can be used
-//! to get and modify the value of the [`TIME_COUNTER`].
+//! The [`elapsed_us()`] and [`take_elapsed_us()`] functions can
+//! be used to get the value of the [`TIME_COUNTER`], the latter
+//! also restarting it from 0.
 
 use core::cell::RefCell;
 use core::sync::atomic::{Ordering::Relaxed, AtomicU32};
@@ -30,7 +31,7 @@ static TIMER: Mutex<RefCell<Option<Timer<Timer0<TIMG0>>>>> = Mutex::new(RefCell:
 static TIME_COUNTER: AtomicU32 = AtomicU32::new(0);
 
 /// Increment [`TIME_COUNTER`] every 100µs
-const PRECISION: u32 = 100;
+pub const PRECISION: u32 = 100;
 
 /// Timer group 0, timer 0 interrupt handler
 #[interrupt]
@@ -61,13 +62,65 @@ pub fn init(mut timer: Timer<Timer0<TIMG0>>) {
     });
 }
 
-/// Restarts the timer from 0
-pub fn reset() {
-    TIME_COUNTER.store(0, Relaxed);
-}
-
 /// Get the amount of micro-seconds elapsed since the start
 /// of the timer.
 pub fn elapsed_us() -> u32 {
     TIME_COUNTER.load(Relaxed) * PRECISION
 }
+
+/// Get the amount of micro-seconds elapsed since the start
+/// of the timer, and restart it from 0.
+///
+/// The counter is read and cleared atomically, so the interrupts
+/// that happen in between are not lost, even when this is called
+/// in a tight loop.
+pub fn take_elapsed_us() -> u32 {
+    TIME_COUNTER.swap(0, Relaxed) * PRECISION
+}
+
+/// Scroll pacing based on the elapsed time
+///
+/// Converts the time elapsed between frames into the number of
+/// pixels to scroll, keeping track of the fractions of pixel left
+/// behind so that the speed stays accurate regardless of how
+/// irregularly [`Scroll::advance`] is called.
+pub struct Scroll {
+    /// Speed in pixels per second
+    speed: u32,
+    /// Elapsed time not yet converted into pixels, in µs times [`Scroll::speed`]
+    pending: u64,
+}
+
+impl Scroll {
+    /// Creates a new [`Scroll`] moving at the given speed in pixels per second
+    pub const fn new(speed: u32) -> Self {
+        Self { speed, pending: 0 }
+    }
+
+    /// Returns the number of pixels to scroll after `elapsed_us` µs
+    /// from the previous call
+    pub fn advance(&mut self, elapsed_us: u32) -> u32 {
+        self.pending += u64::from(elapsed_us) * u64::from(self.speed);
+        let px = self.pending / 1_000_000;
+        self.pending %= 1_000_000;
+        px as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scroll_follows_the_elapsed_time() {
+        let mut scroll = Scroll::new(5);
+        assert_eq!(scroll.advance(100_000), 0);
+        assert_eq!(scroll.advance(100_000), 1);
+        assert_eq!(scroll.advance(1_000_000), 5);
+
+        // The fractions of pixel left behind by irregular frames add up
+        let px: u32 = (0..10).map(|_| scroll.advance(30_000)).sum();
+        assert_eq!(px, 1);
+        assert_eq!(scroll.advance(100_000), 1);
+    }
+}